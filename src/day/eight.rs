@@ -1,9 +1,9 @@
-use std::{path::PathBuf, result, str::FromStr};
+use std::{result, str::FromStr};
 
 use lazy_static::lazy_static;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::Input;
 
 #[derive(thiserror::Error, Debug)]
 enum ParseError {
@@ -26,14 +26,14 @@ type Result<T> = result::Result<T, Error>;
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 }
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
         let mut count = 0;
-        for line in read_lines(&self.input)? {
+        for line in self.input.lines(SAMPLE)? {
             let sample = DisplaySample::parse(&line)?;
             let decoder = DisplayDecoder::build(sample.patterns())?;
             let message = decoder.decode(sample.output())?;
@@ -162,7 +162,7 @@ impl DigitPattern {
     }
 
     fn contains_element(&self, element: u8) -> bool {
-        self.0.iter().any(|self_element| *self_element == element)
+        self.0.contains(&element)
     }
 }
 
@@ -206,6 +206,13 @@ impl DisplaySample {
         if pattern_lens != (*SORTED_DIGIT_PATTERN_LENGTHS) {
             return Err(ParseError::ParseDisplaySampleError(text.to_owned()));
         }
+        if patterns
+            .iter()
+            .enumerate()
+            .any(|(index, pattern)| patterns[(index + 1)..].contains(pattern))
+        {
+            return Err(ParseError::ParseDisplaySampleError(text.to_owned()));
+        }
 
         let output = patterns_and_output[1]
             .split(' ')
@@ -227,9 +234,22 @@ impl DisplaySample {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
+
 #[cfg(test)]
 mod tests {
-    use super::{DigitPattern, DisplayDecoder, DisplaySample};
+    use super::{DigitPattern, DisplayDecoder, DisplaySample, ParseError};
     use std::str::FromStr;
 
     #[test]
@@ -238,7 +258,7 @@ mod tests {
             "be", "cfbegad", "cbdgef", "fgaecd", "cgeb", "fdcge", "agebfd",
             "fecdb", "fabcd", "edb",
         ];
-        let expected_output = vec!["fdgacbe", "cefdb", "cefbgd", "gcbe"];
+        let expected_output = ["fdgacbe", "cefdb", "cefbgd", "gcbe"];
         let text = format!(
             "{} | {}",
             expected_patterns.join(" "),
@@ -259,6 +279,16 @@ mod tests {
                 == DigitPattern::from_str(expected_str).unwrap()));
     }
 
+    #[test]
+    fn display_sample_parse_duplicate_pattern() {
+        let text = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fdcge edb | fdgacbe cefdb cefbgd gcbe";
+
+        assert!(matches!(
+            DisplaySample::parse(text),
+            Err(ParseError::ParseDisplaySampleError(_))
+        ));
+    }
+
     #[test]
     fn display_decoder_decode() {
         let sample =
@@ -1,8 +1,8 @@
-use std::{fmt::Debug, path::PathBuf, str::FromStr};
+use std::{fmt::Debug, io::BufRead, str::FromStr};
 
 use structopt::{self, StructOpt};
 
-use super::read_all_text;
+use super::{grid::Grid, Input};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to parse mode from '{0}'")]
@@ -14,8 +14,8 @@ pub struct ParseOctopusEnergyLevelGridError(String);
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("10"), long)]
     steps: usize,
@@ -28,6 +28,7 @@ pub struct Command {
 pub enum Mode {
     Flashes,
     StepsUntilAllFlash,
+    Both,
 }
 
 impl FromStr for Mode {
@@ -37,6 +38,7 @@ impl FromStr for Mode {
         match s {
             "flashes" => Ok(Mode::Flashes),
             "steps-until-all-flash" => Ok(Mode::StepsUntilAllFlash),
+            "both" => Ok(Mode::Both),
             _ => Err(ParseModeError(s.to_owned())),
         }
     }
@@ -44,13 +46,13 @@ impl FromStr for Mode {
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
-        let mut grid =
-            OctopusEnergyLevelGrid::from_str(&read_all_text(&self.input)?)?;
+        let mut grid = OctopusEnergyLevelGrid::parse_reader(
+            self.input.non_empty_reader(SAMPLE)?,
+        )?;
 
         match self.mode {
             Mode::Flashes => {
-                let flashes = (0..self.steps)
-                    .fold(0, |flashes, _| flashes + grid.step().flashes());
+                let (flashes, _) = grid.simulate(self.steps);
                 println!(
                     "{} flashes occurred after {} steps.",
                     flashes, self.steps
@@ -68,117 +70,45 @@ impl Command {
                 }
                 println!("All octopuses flashed at step {}", steps);
             }
+            Mode::Both => {
+                let (flashes, sync_step) = grid.simulate(self.steps);
+                println!(
+                    "{} flashes occurred after {} steps.",
+                    flashes, self.steps
+                );
+                if let Some(sync_step) = sync_step {
+                    println!("All octopuses flashed at step {}", sync_step);
+                } else {
+                    println!(
+                        "All octopuses did not flash within {} steps",
+                        self.steps
+                    );
+                }
+            }
         }
         Ok(())
     }
 }
 
+/// The energy level of every octopus in the cavern.
 #[derive(Debug)]
-struct OctopusEnergyLevelGrid(Vec<Vec<u8>>);
+pub struct OctopusEnergyLevelGrid(Grid<u8>);
 
 impl OctopusEnergyLevelGrid {
     fn step(&mut self) -> StepStats {
-        self.0
-            .iter_mut()
-            .flat_map(|row| row.iter_mut())
-            .for_each(|cell| *cell += 1);
-
-        let above = |row: usize, col: usize| (row - 1, col);
-        let above_left = |row: usize, col: usize| (row - 1, col - 1);
-        let above_right = |row: usize, col: usize| (row - 1, col + 1);
-        let left = |row: usize, col: usize| (row, col - 1);
-        let right = |row: usize, col: usize| (row, col + 1);
-        let below = |row: usize, col: usize| (row + 1, col);
-        let below_left = |row: usize, col: usize| (row + 1, col - 1);
-        let below_right = |row: usize, col: usize| (row + 1, col + 1);
-
-        let max_row = self.length() - 1;
-        let max_col = self.width() - 1;
+        self.0.cells_mut().for_each(|cell| *cell += 1);
+
         let mut flashes = 0;
         loop {
             let mut flashed = false;
-            for row in 0..=max_row {
-                for col in 0..=max_col {
-                    if self.0[row][col] >= 10 {
-                        flashed = true;
-                        flashes += 1;
-                        self.0[row][col] = 0;
-                        match (row, col) {
-                            // top left
-                            (0, 0) => self.increment_not_flashed(&[
-                                right(0, 0),
-                                below_right(0, 0),
-                                below(0, 0),
-                            ]),
-                            // top right
-                            (0, col) if col == max_col => self
-                                .increment_not_flashed(&[
-                                    left(0, col),
-                                    below(0, col),
-                                    below_left(0, col),
-                                ]),
-                            // bottom right
-                            (row, col) if row == max_row && col == max_col => {
-                                self.increment_not_flashed(&[
-                                    above_left(row, col),
-                                    above(row, col),
-                                    left(row, col),
-                                ])
-                            }
-                            // botton left
-                            (row, 0) if row == max_row => self
-                                .increment_not_flashed(&[
-                                    above(row, 0),
-                                    above_right(row, 0),
-                                    right(row, 0),
-                                ]),
-                            // top
-                            (0, col) => self.increment_not_flashed(&[
-                                left(0, col),
-                                right(0, col),
-                                below_right(0, col),
-                                below(0, col),
-                                below_left(0, col),
-                            ]),
-                            // bottom
-                            (row, col) if row == max_row => self
-                                .increment_not_flashed(&[
-                                    left(row, col),
-                                    above_left(row, col),
-                                    above(row, col),
-                                    above_right(row, col),
-                                    right(row, col),
-                                ]),
-                            // right
-                            (row, col) if col == max_col => self
-                                .increment_not_flashed(&[
-                                    left(row, col),
-                                    above_left(row, col),
-                                    above(row, col),
-                                    below(row, col),
-                                    below_left(row, col),
-                                ]),
-                            // left
-                            (row, 0) => self.increment_not_flashed(&[
-                                above(row, 0),
-                                above_right(row, 0),
-                                right(row, 0),
-                                below_right(row, 0),
-                                below(row, 0),
-                            ]),
-                            // others
-                            (row, col) => self.increment_not_flashed(&[
-                                left(row, col),
-                                above_left(row, col),
-                                above(row, col),
-                                above_right(row, col),
-                                right(row, col),
-                                below_right(row, col),
-                                below(row, col),
-                                below_left(row, col),
-                            ]),
-                        }
-                    }
+            for (row, col) in self.0.positions().collect::<Vec<_>>() {
+                if self.0[(row, col)] >= 10 {
+                    flashed = true;
+                    flashes += 1;
+                    self.0[(row, col)] = 0;
+                    let neighbors =
+                        self.0.neighbors8(row, col).collect::<Vec<_>>();
+                    self.increment_not_flashed(&neighbors);
                 }
             }
             if !flashed {
@@ -188,57 +118,111 @@ impl OctopusEnergyLevelGrid {
         StepStats { flashes }
     }
 
+    /// Steps the grid `steps` times returning the total flashes and the first
+    /// step, if any, at which all octopuses flashed together.
+    fn simulate(&mut self, steps: usize) -> (usize, Option<usize>) {
+        let count = self.width() * self.length();
+        let mut flashes = 0;
+        let mut sync_step = None;
+        for step in 1..=steps {
+            let step_flashes = self.step().flashes();
+            if sync_step.is_none() && step_flashes == count {
+                sync_step = Some(step);
+            }
+            flashes += step_flashes;
+        }
+        (flashes, sync_step)
+    }
+
+    /// Steps the grid up to `max` times returning the first step at which the
+    /// octopus at `row` and `col` flashed, leaving it reset to zero.
+    pub fn first_flash_step(
+        &mut self,
+        row: usize,
+        col: usize,
+        max: usize,
+    ) -> Option<usize> {
+        self.0.get(row, col)?;
+        (1..=max).find(|_| {
+            self.step();
+            self.0[(row, col)] == 0
+        })
+    }
+
+    pub fn cells(&self) -> &[Vec<u8>] {
+        self.0.rows()
+    }
+
     fn width(&self) -> usize {
-        self.0.len()
+        self.0.width()
     }
 
     fn length(&self) -> usize {
-        self.0[0].len()
+        self.0.height()
     }
 
     fn increment_not_flashed(&mut self, cells: &[(usize, usize)]) {
         for (row, col) in cells.iter().copied() {
-            if self.0[row][col] != 0 {
-                self.0[row][col] += 1;
+            if self.0[(row, col)] != 0 {
+                self.0[(row, col)] += 1;
             }
         }
     }
 }
 
-impl FromStr for OctopusEnergyLevelGrid {
-    type Err = ParseOctopusEnergyLevelGridError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut grid = vec![];
-        let mut prior_len = None;
-        for line in s.split('\n') {
+impl OctopusEnergyLevelGrid {
+    /// Parses the grid a line at a time, accepting `\r\n` line endings.
+    fn parse_reader<R: BufRead>(
+        reader: R,
+    ) -> Result<Self, ParseOctopusEnergyLevelGridError> {
+        let mut grid: Vec<Vec<u8>> = vec![];
+        for line in reader.lines() {
+            let line = line.map_err(|err| {
+                ParseOctopusEnergyLevelGridError(err.to_string())
+            })?;
+            let line = line.trim_end_matches('\r');
             if line.is_empty() {
-                if prior_len.is_none() {
+                if grid.is_empty() {
                     continue;
                 } else {
                     break;
                 }
             }
-            prior_len = match prior_len {
-                Some(prior_len) if line.len() != prior_len => {
-                    return Err(ParseOctopusEnergyLevelGridError(s.to_owned()))
-                }
-                None => Some(line.len()),
-                _ => prior_len,
-            };
-            let row_levels = line
-                .as_bytes()
-                .iter()
-                .copied()
-                .map(|b| match b {
-                    b @ b'0'..=b'9' => Ok(b - b'0'),
-                    _ => Err(ParseOctopusEnergyLevelGridError(s.to_owned())),
-                })
-                .collect::<Result<Vec<u8>, Self::Err>>()?;
+            let row_levels = parse_row(line, grid.first().map(Vec::len))
+                .ok_or_else(|| {
+                    ParseOctopusEnergyLevelGridError(line.to_owned())
+                })?;
             grid.push(row_levels);
         }
 
-        Ok(OctopusEnergyLevelGrid(grid))
+        if grid.is_empty() {
+            return Err(ParseOctopusEnergyLevelGridError(String::new()));
+        }
+        Ok(OctopusEnergyLevelGrid(Grid::new(grid)))
+    }
+}
+
+/// Parses a row of energy levels, which must match the `width` of the prior
+/// rows when there are any.
+fn parse_row(line: &str, width: Option<usize>) -> Option<Vec<u8>> {
+    if width.is_some_and(|width| width != line.len()) {
+        return None;
+    }
+    line.as_bytes()
+        .iter()
+        .copied()
+        .map(|b| match b {
+            b @ b'0'..=b'9' => Some(b - b'0'),
+            _ => None,
+        })
+        .collect()
+}
+
+impl FromStr for OctopusEnergyLevelGrid {
+    type Err = ParseOctopusEnergyLevelGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_reader(s.as_bytes())
     }
 }
 
@@ -252,25 +236,68 @@ impl StepStats {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526";
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{io::Cursor, str::FromStr};
 
-    use super::OctopusEnergyLevelGrid;
+    use super::{OctopusEnergyLevelGrid, SAMPLE};
 
     #[test]
     fn octopus_energy_level_grid_from_str_test() {
         let grid =
-            OctopusEnergyLevelGrid::from_str(INPUT).expect("valid input");
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
 
         assert_eq!(grid.width(), 10);
         assert_eq!(grid.length(), 10);
+        assert_eq!(
+            OctopusEnergyLevelGrid::from_str(&SAMPLE.replace('\n', "\r\n"))
+                .expect("valid input")
+                .cells(),
+            grid.cells()
+        );
+    }
+
+    #[test]
+    fn octopus_energy_level_grid_parse_reader_test() {
+        let grid =
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
+        let read_grid = OctopusEnergyLevelGrid::parse_reader(Cursor::new(
+            SAMPLE.replace('\n', "\r\n"),
+        ))
+        .expect("valid input");
+
+        assert_eq!(read_grid.width(), grid.width());
+        assert_eq!(read_grid.length(), grid.length());
+        assert_eq!(read_grid.cells(), grid.cells());
+    }
+
+    #[test]
+    fn octopus_energy_level_grid_first_flash_step_test() {
+        let grid =
+            || OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
+
+        assert_eq!(grid().first_flash_step(0, 2, 10), Some(2));
+        assert_eq!(grid().first_flash_step(0, 0, 10), Some(3));
+        assert_eq!(grid().first_flash_step(0, 0, 2), None);
+        assert_eq!(grid().first_flash_step(10, 0, 10), None);
     }
 
     #[test]
     fn octopus_energy_level_grid_step_test() {
         let mut grid =
-            OctopusEnergyLevelGrid::from_str(INPUT).expect("valid input");
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
 
         let stats = grid.step();
         assert_eq!(stats.flashes(), 0);
@@ -303,10 +330,22 @@ mod tests {
         assert_eq!(stats.flashes(), 29);
     }
 
+    #[test]
+    fn octopus_energy_level_grid_cells_test() {
+        let mut grid =
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
+        let expected = OctopusEnergyLevelGrid::from_str(AFTER_STEP_ONE)
+            .expect("valid input");
+
+        grid.step();
+
+        assert_eq!(grid.cells(), expected.cells());
+    }
+
     #[test]
     fn octopus_energy_level_grid_step_100_test() {
         let mut grid =
-            OctopusEnergyLevelGrid::from_str(INPUT).expect("valid input");
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
 
         let flashes =
             (0..100).fold(0, |flashes, _| flashes + grid.step().flashes());
@@ -317,7 +356,7 @@ mod tests {
     #[test]
     fn octopus_energy_level_grid_step_until_all_flash_test() {
         let mut grid =
-            OctopusEnergyLevelGrid::from_str(INPUT).expect("valid input");
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
         let count = grid.width() * grid.length();
 
         let mut step = 0;
@@ -332,14 +371,36 @@ mod tests {
         assert_eq!(step, 195);
     }
 
-    const INPUT: &str = r"5483143223
-2745854711
-5264556173
-6141336146
-6357385478
-4167524645
-2176841721
-6882881134
-4846848554
-5283751526";
+    #[test]
+    fn octopus_energy_level_grid_simulate_test() {
+        let mut grid =
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
+        let mut expected_grid =
+            OctopusEnergyLevelGrid::from_str(SAMPLE).expect("valid input");
+        let expected_flashes = (0..200)
+            .fold(0, |flashes, _| flashes + expected_grid.step().flashes());
+
+        let (flashes, sync_step) = grid.simulate(200);
+
+        assert_eq!(flashes, expected_flashes);
+        assert_eq!(sync_step, Some(195));
+    }
+
+    const AFTER_STEP_ONE: &str = r"6594254334
+3856965822
+6375667284
+7252447257
+7468496589
+5278635756
+3287952832
+7993992245
+5957959665
+6394862637";
+
+    #[test]
+    fn octopus_energy_level_grid_parse_empty() {
+        assert!(OctopusEnergyLevelGrid::from_str("").is_err());
+        assert!(OctopusEnergyLevelGrid::from_str("\n").is_err());
+        assert!(OctopusEnergyLevelGrid::parse_reader(Cursor::new("")).is_err());
+    }
 }
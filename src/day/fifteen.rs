@@ -1,53 +1,135 @@
-use std::{cmp::Ordering, collections::BinaryHeap, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::BinaryHeap,
+    time::{Duration, Instant},
+};
 
+use serde::Serialize;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{Format, Input};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(long)]
-    full: bool
+    full: bool,
+
+    #[structopt(long)]
+    compare: bool,
+
+    #[structopt(long)]
+    wrap: bool,
+
+    #[structopt(long)]
+    baseline: bool,
+
+    #[structopt(default_value("1"), long)]
+    repeats: usize,
+
+    /// Only look for paths with a risk of at most this
+    #[structopt(long, conflicts_with("compare"))]
+    bound: Option<usize>,
 }
 
 impl Command {
-    pub fn run(&self) -> anyhow::Result<()> {
-        let floor = CaveFloor::parse(
-            read_lines(&self.input)?.iter().map(String::as_str),
-            self.full
+    pub fn run(&self, format: Format) -> anyhow::Result<()> {
+        let mut floor = CaveFloor::parse(
+            self.input.non_empty_text(SAMPLE)?.lines(),
+            self.full,
         )?;
+        floor.set_wrap(self.wrap);
+
+        if self.compare {
+            let searches = floor.compare_searches()?;
+            for (name, result) in searches.iter() {
+                match result.risk {
+                    Some(risk) => println!(
+                        "{}: least risky path value {} ({} nodes visited)",
+                        name, risk, result.visited_nodes
+                    ),
+                    None => println!(
+                        "{}: no path out of here ({} nodes visited)",
+                        name, result.visited_nodes
+                    ),
+                }
+            }
+            if !searches_agree(&searches) {
+                eprintln!("warning: search algorithms disagree");
+            }
+            return Ok(());
+        }
 
-        if let Some(least_path_risk) = floor.least_risk_path_value() {
+        let (least_path_risk, average) =
+            floor.repeated_least_risk_path_value(self.repeats, self.bound)?;
+        if format == Format::Json {
+            let risks = Risks {
+                least_risk: least_path_risk,
+                straight_path_risk: self
+                    .baseline
+                    .then(|| floor.straight_path_risk()),
+            };
+            println!("{}", serde_json::to_string(&risks)?);
+            return Ok(());
+        }
+        if let Some(least_path_risk) = least_path_risk {
             println!("Least risky path value: {}", least_path_risk);
+        } else if let Some(bound) = self.bound {
+            println!("There's no path out of here within a risk of {}", bound);
         } else {
             println!("There's no path out of here");
         }
-        
+        if self.repeats > 1 {
+            println!(
+                "Average solve time over {} repeats: {:?}",
+                self.repeats, average
+            );
+        }
+        if self.baseline {
+            println!("Straight path value: {}", floor.straight_path_risk());
+        }
 
         Ok(())
     }
 }
 
+/// The `--format json` answer. The least risk is `null` when there's no
+/// path out, and the straight path risk is only given with `--baseline`.
+#[derive(Debug, Serialize)]
+struct Risks {
+    least_risk: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    straight_path_risk: Option<usize>,
+}
+
 struct CaveFloor {
     nodes: Vec<Vec<u8>>,
     length: usize,
     width: usize,
+    wrap: bool,
+    /// The lowest risk of any position, which every step costs at least.
+    min_risk: usize,
 }
 
 impl CaveFloor {
     fn new(nodes: Vec<Vec<u8>>, width: usize) -> Self {
         let length = nodes.len();
+        let min_risk = nodes.iter().flatten().min().copied().unwrap_or(0);
         CaveFloor {
             nodes,
             length,
             width,
+            wrap: false,
+            min_risk: min_risk as usize,
         }
     }
 
-    fn parse<'iter, Iter>(lines: Iter, full: bool) -> Result<Self, ParseCaveFloorError>
+    fn parse<'iter, Iter>(
+        lines: Iter,
+        full: bool,
+    ) -> Result<Self, ParseCaveFloorError>
     where
         Iter: Iterator<Item = &'iter str>,
     {
@@ -56,7 +138,6 @@ impl CaveFloor {
         for line in lines {
             let line_levels = line
                 .chars()
-                .into_iter()
                 .map(|c| match c {
                     n @ '0'..='9' => Ok((n as u8) - b'0'),
                     _ => Err(ParseCaveFloorError::new(line)),
@@ -75,28 +156,38 @@ impl CaveFloor {
         if full {
             let inc_or_wrap = |inc: u8, value: &u8| {
                 let new_value = *value + inc;
-                if new_value <= 9 { new_value } else { new_value - 9 }
+                if new_value <= 9 {
+                    new_value
+                } else {
+                    new_value - 9
+                }
             };
             let template = risk_levels.clone();
             for increment in 1u8..=4 {
                 for (row, row_risk_levels) in template.iter().enumerate() {
-                    risk_levels[row].append(&mut row_risk_levels.iter()
-                        .map(|risk| inc_or_wrap(increment, risk)).collect());
+                    risk_levels[row].append(
+                        &mut row_risk_levels
+                            .iter()
+                            .map(|risk| inc_or_wrap(increment, risk))
+                            .collect(),
+                    );
                 }
             }
             let template = risk_levels.clone();
             for increment in 1u8..=4 {
                 for row_risk_levels in template.iter() {
-                    risk_levels.push(row_risk_levels.iter()
-                        .map(|risk| inc_or_wrap(increment, risk)).collect());
+                    risk_levels.push(
+                        row_risk_levels
+                            .iter()
+                            .map(|risk| inc_or_wrap(increment, risk))
+                            .collect(),
+                    );
                 }
             }
             line_len = line_len.map(|len| len * 5);
         }
-        Ok(CaveFloor::new(
-            risk_levels,
-            line_len.expect("there's at least one line"),
-        ))
+        let width = line_len.ok_or_else(|| ParseCaveFloorError::new(""))?;
+        Ok(CaveFloor::new(risk_levels, width))
     }
 
     fn edges(&self) -> Vec<Vec<Edge>> {
@@ -106,7 +197,22 @@ impl CaveFloor {
             .collect()
     }
 
+    /// Wrapping connects the opposite sides of the floor, turning it into a
+    /// torus.
+    fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
     fn node_edges(&self, row: usize, column: usize) -> Vec<Edge> {
+        if self.wrap {
+            return vec![
+                self.edge(row, (column + self.width - 1) % self.width),
+                self.edge((row + self.length - 1) % self.length, column),
+                self.edge(row, (column + 1) % self.width),
+                self.edge((row + 1) % self.length, column),
+            ];
+        }
+
         let mut edges = vec![];
         // left edge
         if column != 0 {
@@ -134,31 +240,311 @@ impl CaveFloor {
     }
 
     fn least_risk_path_value(&self) -> Option<usize> {
+        self.dijkstra(None).risk
+    }
+
+    /// Sums the risks along the top row and then down the last column, a
+    /// naive path to compare the least risky path against.
+    fn straight_path_risk(&self) -> usize {
+        let top_row = self.nodes[0][1..].iter();
+        let last_column =
+            self.nodes[1..].iter().map(|row| &row[self.width - 1]);
+        top_row.chain(last_column).map(|risk| *risk as usize).sum()
+    }
+
+    /// Solves `repeats` times, at least once, returning the least risky path
+    /// value, within `bound` when there is one, along with the average time a
+    /// solve took.
+    fn repeated_least_risk_path_value(
+        &self,
+        repeats: usize,
+        bound: Option<usize>,
+    ) -> Result<(Option<usize>, Duration), InconsistentSolveError> {
+        let solve = || match bound {
+            Some(bound) => self.least_risk_bounded(bound),
+            None => self.least_risk_path_value(),
+        };
+        let repeats = repeats.max(1);
+        let start = Instant::now();
+        let risk = solve();
+        for _ in 1..repeats {
+            if solve() != risk {
+                return Err(InconsistentSolveError);
+            }
+        }
+        Ok((risk, start.elapsed() / repeats as u32))
+    }
+
+    fn goal(&self) -> usize {
+        self.width * self.length - 1
+    }
+
+    /// Never explores paths costing more than `bound`, when there is one.
+    fn dijkstra(&self, bound: Option<usize>) -> SearchResult {
         let start = 0;
-        let goal = self.width * self.length - 1;
+        let goal = self.goal();
         let edges = self.edges();
         let mut dist: Vec<_> = (0..edges.len()).map(|_| usize::MAX).collect();
         let mut heap = BinaryHeap::new();
+        let mut visited_nodes = 0;
 
         dist[start] = 0;
-        heap.push(State { cost: 0, position: start });
+        heap.push(State {
+            cost: 0,
+            position: start,
+        });
 
         while let Some(State { cost, position }) = heap.pop() {
-            if position == goal { return Some(cost); }
+            if cost > dist[position] {
+                continue;
+            }
 
-            if cost > dist[position] { continue; }
+            visited_nodes += 1;
+            if position == goal {
+                return SearchResult::found(cost, visited_nodes);
+            }
 
             for edge in &edges[position] {
-                let next = State { cost: cost + edge.risk as usize, position: edge.node };
+                let next = State {
+                    cost: cost + edge.risk as usize,
+                    position: edge.node,
+                };
+
+                if bound.is_none_or(|bound| next.cost <= bound)
+                    && next.cost < dist[next.position]
+                {
+                    heap.push(next);
+                    dist[next.position] = next.cost;
+                }
+            }
+        }
+
+        SearchResult::not_found(visited_nodes)
+    }
+
+    /// Dijkstra's search giving up with `None` once every remaining path
+    /// costs more than `bound`.
+    fn least_risk_bounded(&self, bound: usize) -> Option<usize> {
+        self.dijkstra(Some(bound)).risk
+    }
+
+    /// Manhattan distance to the goal scaled by the lowest risk on the
+    /// floor, which every step costs at least, so this never overestimates
+    /// the remaining risk. On a wrapped floor either way around may be
+    /// shorter.
+    fn heuristic(&self, position: usize) -> usize {
+        let (row, column) = (position / self.width, position % self.width);
+        let (rows, columns) = (self.length - 1 - row, self.width - 1 - column);
+        let steps = if self.wrap {
+            rows.min(self.length - rows) + columns.min(self.width - columns)
+        } else {
+            rows + columns
+        };
+        steps * self.min_risk
+    }
+
+    fn a_star(&self) -> SearchResult {
+        let start = 0;
+        let goal = self.goal();
+        let edges = self.edges();
+        let mut dist: Vec<_> = (0..edges.len()).map(|_| usize::MAX).collect();
+        let mut heap = BinaryHeap::new();
+        let mut visited_nodes = 0;
+
+        dist[start] = 0;
+        heap.push(State {
+            cost: self.heuristic(start),
+            position: start,
+        });
+
+        while let Some(State { cost, position }) = heap.pop() {
+            if cost > dist[position] + self.heuristic(position) {
+                continue;
+            }
+
+            visited_nodes += 1;
+            if position == goal {
+                return SearchResult::found(dist[position], visited_nodes);
+            }
+
+            for edge in &edges[position] {
+                let next_cost = dist[position] + edge.risk as usize;
+                if next_cost < dist[edge.node] {
+                    dist[edge.node] = next_cost;
+                    heap.push(State {
+                        cost: next_cost + self.heuristic(edge.node),
+                        position: edge.node,
+                    });
+                }
+            }
+        }
+
+        SearchResult::not_found(visited_nodes)
+    }
+
+    /// Runs Dijkstra from both the start and the goal, stopping once the
+    /// frontiers can no longer improve on the best path through a node
+    /// reached by both searches.
+    fn bidirectional(&self) -> SearchResult {
+        let start = 0;
+        let goal = self.goal();
+        let edges = self.edges();
+        let mut forward_dist: Vec<_> =
+            (0..edges.len()).map(|_| usize::MAX).collect();
+        let mut backward_dist = forward_dist.clone();
+        let mut forward_heap = BinaryHeap::new();
+        let mut backward_heap = BinaryHeap::new();
+        let mut best = if start == goal { Some(0) } else { None };
+        let mut visited_nodes = 0;
+
+        forward_dist[start] = 0;
+        forward_heap.push(State {
+            cost: 0,
+            position: start,
+        });
+        backward_dist[goal] = 0;
+        backward_heap.push(State {
+            cost: 0,
+            position: goal,
+        });
+
+        while let (Some(forward), Some(backward)) =
+            (forward_heap.peek().copied(), backward_heap.peek().copied())
+        {
+            if let Some(best) = best {
+                if forward.cost + backward.cost >= best {
+                    break;
+                }
+            }
+
+            let forward_step = forward.cost <= backward.cost;
+            let State { cost, position } = if forward_step {
+                forward_heap.pop()
+            } else {
+                backward_heap.pop()
+            }
+            .expect("peeked");
+            let (dist, other_dist, heap) = if forward_step {
+                (&mut forward_dist, &backward_dist, &mut forward_heap)
+            } else {
+                (&mut backward_dist, &forward_dist, &mut backward_heap)
+            };
+            if cost > dist[position] {
+                continue;
+            }
+
+            visited_nodes += 1;
+            for edge in &edges[position] {
+                // moving backwards enters the current node rather than the
+                // adjoining one, so it pays the current node's risk
+                let risk = if forward_step {
+                    edge.risk
+                } else {
+                    self.nodes[position / self.width][position % self.width]
+                };
+                let next = State {
+                    cost: cost + risk as usize,
+                    position: edge.node,
+                };
 
                 if next.cost < dist[next.position] {
                     heap.push(next);
                     dist[next.position] = next.cost;
                 }
+                if other_dist[next.position] != usize::MAX {
+                    let through = next.cost + other_dist[next.position];
+                    if best.is_none_or(|best| through < best) {
+                        best = Some(through);
+                    }
+                }
             }
         }
 
-        None
+        match best {
+            Some(risk) => SearchResult::found(risk, visited_nodes),
+            None => SearchResult::not_found(visited_nodes),
+        }
+    }
+
+    /// Relaxes every edge until the distances settle. The risks are never
+    /// negative but the search still rejects a negative cycle rather than
+    /// looping forever. Each node relaxed from counts as a visit, once per
+    /// pass.
+    fn least_risk_bellman_ford(
+        &self,
+    ) -> Result<SearchResult, NegativeCycleError> {
+        let goal = self.goal();
+        let edges = self.edges();
+        let mut dist: Vec<Option<i64>> = vec![None; edges.len()];
+        let mut visited_nodes = 0;
+        dist[0] = Some(0);
+
+        for _ in 0..edges.len() {
+            let mut relaxed = false;
+            for (position, position_edges) in edges.iter().enumerate() {
+                let cost = match dist[position] {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+                visited_nodes += 1;
+                for edge in position_edges {
+                    let next_cost = cost + edge.risk as i64;
+                    if dist[edge.node].is_none_or(|dist| next_cost < dist) {
+                        dist[edge.node] = Some(next_cost);
+                        relaxed = true;
+                    }
+                }
+            }
+            if !relaxed {
+                return Ok(match dist[goal] {
+                    Some(cost) => {
+                        SearchResult::found(cost as usize, visited_nodes)
+                    }
+                    None => SearchResult::not_found(visited_nodes),
+                });
+            }
+        }
+
+        Err(NegativeCycleError)
+    }
+
+    fn compare_searches(
+        &self,
+    ) -> Result<Vec<(&'static str, SearchResult)>, NegativeCycleError> {
+        Ok(vec![
+            ("dijkstra", self.dijkstra(None)),
+            ("a-star", self.a_star()),
+            ("bidirectional", self.bidirectional()),
+            ("bellman-ford", self.least_risk_bellman_ford()?),
+        ])
+    }
+}
+
+fn searches_agree(searches: &[(&str, SearchResult)]) -> bool {
+    searches
+        .windows(2)
+        .all(|pair| pair[0].1.risk == pair[1].1.risk)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SearchResult {
+    risk: Option<usize>,
+    visited_nodes: usize,
+}
+
+impl SearchResult {
+    fn found(risk: usize, visited_nodes: usize) -> Self {
+        SearchResult {
+            risk: Some(risk),
+            visited_nodes,
+        }
+    }
+
+    fn not_found(visited_nodes: usize) -> Self {
+        SearchResult {
+            risk: None,
+            visited_nodes,
+        }
     }
 }
 
@@ -170,12 +556,14 @@ struct Edge {
 #[derive(Copy, Clone, Eq, PartialEq)]
 struct State {
     cost: usize,
-    position: usize
+    position: usize,
 }
 
 impl Ord for State {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
+        other
+            .cost
+            .cmp(&self.cost)
             .then_with(|| self.position.cmp(&other.position))
     }
 }
@@ -186,6 +574,14 @@ impl PartialOrd for State {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error("Cave floor has a negative risk cycle")]
+pub struct NegativeCycleError;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Repeated solves found different least risky path values")]
+pub struct InconsistentSolveError;
+
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to parse cave floor from '{0}'")]
 pub struct ParseCaveFloorError(String);
@@ -195,32 +591,180 @@ impl ParseCaveFloorError {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581";
+
 #[cfg(test)]
 mod tests {
-    use super::CaveFloor;
+    use super::{searches_agree, CaveFloor, Risks, SAMPLE};
+
+    #[test]
+    fn risks_json() {
+        let risks = Risks {
+            least_risk: None,
+            straight_path_risk: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&risks).expect("serializable"),
+            r#"{"least_risk":null}"#
+        );
+
+        let risks = Risks {
+            least_risk: Some(40),
+            straight_path_risk: Some(45),
+        };
+        assert_eq!(
+            serde_json::to_string(&risks).expect("serializable"),
+            r#"{"least_risk":40,"straight_path_risk":45}"#
+        );
+    }
 
     #[test]
     fn least_risk_path_value() {
-        let floor = CaveFloor::parse(INPUT.split('\n'), false).expect("valid input");
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
 
         assert_eq!(Some(40), floor.least_risk_path_value());
     }
 
     #[test]
     fn full_least_risk_path_value() {
-        let floor = CaveFloor::parse(INPUT.split('\n'), true).expect("valid input");
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), true).expect("valid input");
 
         assert_eq!(Some(315), floor.least_risk_path_value());
     }
 
-    const INPUT: &str = "1163751742
-1381373672
-2136511328
-3694931569
-7463417111
-1319128137
-1359912421
-3125421639
-1293138521
-2311944581";
+    #[test]
+    fn repeated_least_risk_path_value() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
+
+        let (risk, _) = floor
+            .repeated_least_risk_path_value(3, None)
+            .expect("consistent solves");
+
+        assert_eq!(risk, floor.least_risk_path_value());
+        assert_eq!(risk, Some(40));
+
+        let (risk, _) = floor
+            .repeated_least_risk_path_value(3, Some(39))
+            .expect("consistent solves");
+        assert_eq!(risk, None);
+    }
+
+    #[test]
+    fn straight_path_risk() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
+
+        // 36 along the top row and 39 down the last column
+        assert_eq!(75, floor.straight_path_risk());
+        assert!(floor.straight_path_risk() > 40);
+    }
+
+    #[test]
+    fn least_risk_bounded() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
+
+        assert_eq!(Some(40), floor.least_risk_bounded(1000));
+        assert_eq!(Some(40), floor.least_risk_bounded(40));
+        assert_eq!(None, floor.least_risk_bounded(39));
+    }
+
+    #[test]
+    fn least_risk_bellman_ford() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
+        let full_floor =
+            CaveFloor::parse(SAMPLE.split('\n'), true).expect("valid input");
+
+        assert_eq!(
+            Some(40),
+            floor
+                .least_risk_bellman_ford()
+                .expect("no negative cycle")
+                .risk
+        );
+        assert_eq!(
+            Some(315),
+            full_floor
+                .least_risk_bellman_ford()
+                .expect("no negative cycle")
+                .risk
+        );
+    }
+
+    #[test]
+    fn wrapped_least_risk_path_value() {
+        let mut floor =
+            CaveFloor::parse(["199", "999", "191"].into_iter(), false)
+                .expect("valid input");
+
+        assert_eq!(Some(20), floor.least_risk_path_value());
+
+        floor.set_wrap(true);
+        assert_eq!(Some(2), floor.least_risk_path_value());
+        assert!(floor
+            .compare_searches()
+            .expect("no negative cycle")
+            .iter()
+            .all(|(_, result)| result.risk == Some(2)));
+    }
+
+    #[test]
+    fn compare_searches() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), false).expect("valid input");
+
+        let searches = floor.compare_searches().expect("no negative cycle");
+
+        assert_eq!(searches.len(), 4);
+        assert!(searches.iter().all(|(_, result)| result.risk == Some(40)));
+        assert!(searches_agree(&searches));
+    }
+
+    #[test]
+    fn compare_searches_with_zero_risks() {
+        let floor = CaveFloor::parse(
+            [
+                "00191109", "00010901", "01011009", "00000001", "09001109",
+                "00109000", "00100101", "09009190",
+            ]
+            .into_iter(),
+            false,
+        )
+        .expect("valid input");
+
+        let searches = floor.compare_searches().expect("no negative cycle");
+
+        assert!(searches.iter().all(|(_, result)| result.risk == Some(1)));
+        assert!(searches_agree(&searches));
+    }
+
+    #[test]
+    fn full_compare_searches() {
+        let floor =
+            CaveFloor::parse(SAMPLE.split('\n'), true).expect("valid input");
+
+        let searches = floor.compare_searches().expect("no negative cycle");
+
+        assert!(searches.iter().all(|(_, result)| result.risk == Some(315)));
+    }
+
+    #[test]
+    fn cave_floor_parse_empty() {
+        assert!(CaveFloor::parse("".lines(), false).is_err());
+        assert!(CaveFloor::parse("".lines(), true).is_err());
+    }
 }
@@ -1,53 +1,61 @@
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use regex::Regex;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{geometry, Day, Input};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 }
 
 impl Command {
     pub fn run(&self) -> Result<()> {
-        let lines =
-            parse_lines(read_lines(&self.input)?.iter().map(String::as_ref))?;
-        let lines: Vec<Line> = lines
-            .into_iter()
-            .filter(|line| {
-                line.is_horizontal() || line.is_vertical() || line.is_diagonal()
-            })
-            .collect();
-        let extents = lines
-            .iter()
-            .skip(1)
-            .fold(lines[0].extents(), |extents, line| {
-                extents.union(&line.extents())
-            });
-        let mut grid = Grid::new(&extents);
-        for line in lines {
-            grid.apply(line);
-        }
-        let dangerous_sector_count =
-            grid.sectors.iter().fold(0, |count, row| {
-                row.iter().fold(count, |count, sector| {
-                    if *sector >= 2 {
-                        count + 1
-                    } else {
-                        count
-                    }
-                })
-            });
-        println!("Sectors with two or more vents: {}", dangerous_sector_count);
+        println!(
+            "Sectors with two or more vents: {}",
+            self.part_two(&self.input.text(SAMPLE)?)?
+        );
 
         Ok(())
     }
 }
 
+impl Day for Command {
+    fn part_one(&self, input: &str) -> Result<String> {
+        Ok(dangerous_sector_count(parse_lines(input.lines())?, false)?
+            .to_string())
+    }
+
+    fn part_two(&self, input: &str) -> Result<String> {
+        Ok(dangerous_sector_count(parse_lines(input.lines())?, true)?
+            .to_string())
+    }
+}
+
+/// Counts the sectors covered by at least two of the horizontal and
+/// vertical lines, and the diagonal lines too when `diagonals` is set.
+fn dangerous_sector_count(lines: Vec<Line>, diagonals: bool) -> Result<usize> {
+    let lines: Vec<Line> = lines
+        .into_iter()
+        .filter(|line| {
+            line.is_horizontal()
+                || line.is_vertical()
+                || (diagonals && line.is_diagonal())
+        })
+        .collect();
+    let extents = lines
+        .iter()
+        .map(Line::extents)
+        .reduce(|extents, line_extents| extents.union(&line_extents))
+        .ok_or_else(|| anyhow!("there are no vent lines"))?;
+    let mut grid = Grid::new(&extents);
+    for line in lines {
+        grid.apply(line);
+    }
+    Ok(grid.cells().filter(|(_, count)| *count >= 2).count())
+}
+
 fn parse_lines<'a, Iter>(lines: Iter) -> Result<Vec<Line>>
 where
     Iter: Iterator<Item = &'a str>,
@@ -106,8 +114,57 @@ impl Grid {
 
     fn apply(&mut self, line: Line) {
         for point in line.path() {
-            self.sectors[point.y - self.origin.y][point.x - self.origin.y] += 1;
+            self.sectors[point.y - self.origin.y][point.x - self.origin.x] += 1;
+        }
+    }
+
+    /// Adds the vent counts of `other` to the matching sectors of this grid.
+    /// `other` must lie entirely within this grid's extents.
+    #[cfg(test)]
+    fn merge(&mut self, other: &Grid) -> Result<()> {
+        let height = self.sectors.len();
+        let width = self.sectors.first().map_or(0, Vec::len);
+        let other_height = other.sectors.len();
+        let other_width = other.sectors.first().map_or(0, Vec::len);
+        if other.origin.x < self.origin.x
+            || other.origin.y < self.origin.y
+            || other.origin.x + other_width > self.origin.x + width
+            || other.origin.y + other_height > self.origin.y + height
+        {
+            return Err(anyhow!(
+                "grid at {:?} sized {}x{} doesn't fit within grid at {:?} sized {}x{}",
+                other.origin,
+                other_width,
+                other_height,
+                self.origin,
+                width,
+                height
+            ));
         }
+
+        for (point, count) in other.cells() {
+            self.sectors[point.y - self.origin.y][point.x - self.origin.x] +=
+                count;
+        }
+        Ok(())
+    }
+
+    /// Iterates over each sector's absolute point and vent count.
+    fn cells(&self) -> impl Iterator<Item = (Point, usize)> + '_ {
+        self.sectors
+            .iter()
+            .enumerate()
+            .flat_map(move |(row, sectors)| {
+                sectors.iter().enumerate().map(move |(column, count)| {
+                    (
+                        Point {
+                            x: self.origin.x + column,
+                            y: self.origin.y + row,
+                        },
+                        *count,
+                    )
+                })
+            })
     }
 }
 
@@ -224,15 +281,117 @@ impl Line {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd)]
-struct Point {
-    x: usize,
-    y: usize,
-}
+type Point = geometry::Point<usize>;
+
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_lines, Line, Point};
+    use structopt::StructOpt;
+
+    use super::{
+        parse_lines, Command, Day, Extents, Grid, Line, Point, SAMPLE,
+    };
+
+    #[test]
+    fn grid_cells_test() {
+        let lines = parse_lines(SAMPLE.split('\n')).expect("valid input");
+        let extents = lines
+            .iter()
+            .skip(1)
+            .fold(lines[0].extents(), |extents, line| {
+                extents.union(&line.extents())
+            });
+        let mut grid = Grid::new(&extents);
+        for line in lines {
+            grid.apply(line);
+        }
+
+        let total: usize = grid.sectors.iter().flatten().sum();
+        assert_eq!(grid.cells().map(|(_, count)| count).sum::<usize>(), total);
+        assert_eq!(grid.cells().filter(|(_, count)| *count >= 2).count(), 12);
+    }
+
+    #[test]
+    fn grid_cells_offset_test() {
+        let extents = Extents::new(Point { x: 3, y: 5 }, Point { x: 4, y: 6 });
+        let mut grid = Grid::new(&extents);
+        grid.apply(Line(Point { x: 4, y: 5 }, Point { x: 4, y: 6 }));
+
+        let cells = grid.cells().collect::<Vec<_>>();
+
+        assert_eq!(
+            cells,
+            vec![
+                (Point { x: 3, y: 5 }, 0),
+                (Point { x: 4, y: 5 }, 1),
+                (Point { x: 3, y: 6 }, 0),
+                (Point { x: 4, y: 6 }, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_merge_test() {
+        let lines = parse_lines(SAMPLE.split('\n')).expect("valid input");
+        let extents = lines
+            .iter()
+            .skip(1)
+            .fold(lines[0].extents(), |extents, line| {
+                extents.union(&line.extents())
+            });
+        let mut combined = Grid::new(&extents);
+        let mut straight = Grid::new(&extents);
+        let mut diagonal = Grid::new(&extents);
+        for line in lines {
+            combined.apply(line);
+            if line.is_horizontal() || line.is_vertical() {
+                straight.apply(line);
+            } else {
+                diagonal.apply(line);
+            }
+        }
+
+        straight.merge(&diagonal).expect("same extents");
+
+        assert_eq!(straight.sectors, combined.sectors);
+        assert_eq!(
+            straight.cells().filter(|(_, count)| *count >= 2).count(),
+            12
+        );
+    }
+
+    #[test]
+    fn grid_merge_incompatible_test() {
+        let mut grid = Grid::new(&Extents::new(
+            Point { x: 3, y: 5 },
+            Point { x: 4, y: 6 },
+        ));
+        let mut inner = Grid::new(&Extents::new(
+            Point { x: 4, y: 5 },
+            Point { x: 4, y: 6 },
+        ));
+        inner.apply(Line(Point { x: 4, y: 5 }, Point { x: 4, y: 6 }));
+        let outer = Grid::new(&Extents::new(
+            Point { x: 2, y: 5 },
+            Point { x: 4, y: 6 },
+        ));
+
+        grid.merge(&inner).expect("inner grid fits");
+
+        assert_eq!(grid.sectors, vec![vec![0, 1], vec![0, 1]]);
+        assert!(grid.merge(&outer).is_err());
+    }
 
     #[test]
     fn parse_lines_test() {
@@ -310,4 +469,13 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn day_parts() {
+        let command = Command::from_iter_safe(["five", "--sample"])
+            .expect("valid arguments");
+
+        assert_eq!(command.part_one(SAMPLE).expect("valid input"), "5");
+        assert_eq!(command.part_two(SAMPLE).expect("valid input"), "12");
+    }
 }
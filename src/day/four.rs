@@ -1,59 +1,93 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use colored::*;
+use serde::Deserialize;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{read_all_text, read_lines};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(required_unless_one(&["json", "sample"]), parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Use the puzzle's example input instead of a file
+    #[structopt(long, conflicts_with_all(&["input", "json"]))]
+    sample: bool,
+
+    #[structopt(long, parse(from_os_str), conflicts_with("input"))]
+    json: Option<PathBuf>,
 
     #[structopt(long)]
     last: bool,
+
+    #[structopt(long)]
+    marked_only: bool,
+
+    /// Count a fully marked diagonal as a win too
+    #[structopt(long)]
+    diagonals: bool,
+
+    /// Print the boards without color or bold
+    #[structopt(long)]
+    no_color: bool,
+
+    /// List the boards that still haven't won once every number is drawn
+    #[structopt(long)]
+    report_losers: bool,
 }
 
 impl Command {
     pub fn run(&self) -> Result<()> {
-        let owned_lines = read_lines(&self.input)?;
-        let lines = owned_lines
-            .iter()
-            .map(String::as_str)
-            .collect::<Vec<&str>>();
-
-        let drawn_numbers = parse_numbers(lines[0])?;
-        let mut boards = parse_boards(&lines[1..])?;
-
-        for number in drawn_numbers {
-            for board in boards.iter_mut() {
-                board.mark_number(number);
+        if self.no_color {
+            colored::control::set_override(false);
+        }
+        let (drawn_numbers, mut boards) = match (&self.input, &self.json) {
+            _ if self.sample => {
+                from_lines(&SAMPLE.lines().collect::<Vec<&str>>())?
             }
+            (_, Some(json)) => from_json(&read_all_text(json)?)?,
+            (Some(input), None) => {
+                let owned_lines = read_lines(input)?;
+                from_lines(
+                    &owned_lines
+                        .iter()
+                        .map(String::as_str)
+                        .collect::<Vec<&str>>(),
+                )?
+            }
+            (None, None) => return Err(anyhow!("missing input")),
+        };
 
-            let winning_boards = boards
-                .iter()
-                .enumerate()
-                .filter(|(_, board)| board.is_winner())
-                .map(|(index, _)| index)
-                .collect::<Vec<usize>>();
-            if !winning_boards.is_empty() {
-                if self.last && boards.len() != 1 {
-                    winning_boards.iter().rev().for_each(|index| {
-                        boards.remove(*index);
-                    });
-                    continue;
-                }
-                println!("winning boards:");
-                winning_boards.iter().for_each(|index| {
-                    let board = &boards[*index];
-                    println!("{}", board);
-                    let sum_unmarked = board.sum_unmarked_numbers();
-                    println!("sum of unmarked numbers is: {}", sum_unmarked);
-                    println!("measure: {}", sum_unmarked * number as i32);
-                });
-
-                break;
+        if self.diagonals {
+            boards.iter_mut().for_each(Board::count_diagonals);
+        }
+        match play(&drawn_numbers, &mut boards, self.last) {
+            Some(result) => {
+                println!(
+                    "winning number: {}",
+                    result.winning_number.to_string().bold()
+                );
+                println!("winning board {}:", result.board_index);
+                println!(
+                    "{}",
+                    boards[result.board_index].render(self.marked_only)
+                );
+                println!("sum of unmarked numbers is: {}", result.sum_unmarked);
+                println!("measure: {}", result.score);
+                println!("won after {} numbers drawn", result.turns);
+            }
+            None => println!("no board wins"),
+        }
+        if self.report_losers {
+            let losers = losers(&drawn_numbers, &mut boards);
+            if losers.is_empty() {
+                println!("every board wins");
+            } else {
+                let indices =
+                    losers.iter().map(usize::to_string).collect::<Vec<_>>();
+                println!("boards that never win: {}", indices.join(", "));
             }
         }
 
@@ -61,6 +95,64 @@ impl Command {
     }
 }
 
+/// The board that won and how it scored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BingoResult {
+    board_index: usize,
+    winning_number: u8,
+    /// How many numbers had been drawn when the board won.
+    turns: usize,
+    sum_unmarked: i32,
+    score: i32,
+}
+
+/// Draws the numbers until the first board wins, or with `last` until every
+/// board has won. When several boards complete on the same draw the one
+/// listed first is reported.
+fn play(
+    numbers: &[u8],
+    boards: &mut [Board],
+    last: bool,
+) -> Option<BingoResult> {
+    let mut won = vec![false; boards.len()];
+    for (turn, &number) in numbers.iter().enumerate() {
+        boards
+            .iter_mut()
+            .for_each(|board| board.mark_number(number));
+        let winners = (0..boards.len())
+            .filter(|index| !won[*index] && boards[*index].is_winner())
+            .collect::<Vec<usize>>();
+        let Some(&board_index) = winners.first() else {
+            continue;
+        };
+        winners.iter().for_each(|index| won[*index] = true);
+        if !last || won.iter().all(|won| *won) {
+            let sum_unmarked = boards[board_index].sum_unmarked_numbers();
+            return Some(BingoResult {
+                board_index,
+                winning_number: number,
+                turns: turn + 1,
+                sum_unmarked,
+                score: sum_unmarked * number as i32,
+            });
+        }
+    }
+    None
+}
+
+/// Draws every number, carrying on from wherever `play` stopped, and returns
+/// the indices of the boards that never win.
+fn losers(numbers: &[u8], boards: &mut [Board]) -> Vec<usize> {
+    for &number in numbers {
+        boards
+            .iter_mut()
+            .for_each(|board| board.mark_number(number));
+    }
+    (0..boards.len())
+        .filter(|index| !boards[*index].is_winner())
+        .collect()
+}
+
 fn parse_numbers(line: &str) -> Result<Vec<u8>> {
     let mut numbers: Vec<u8> = Vec::new();
     for item in line.split(',') {
@@ -71,10 +163,12 @@ fn parse_numbers(line: &str) -> Result<Vec<u8>> {
     Ok(numbers)
 }
 
+/// Parses the blank line separated boards. The first board's row count sets
+/// the size every board must have.
 fn parse_boards(lines: &[&str]) -> Result<Vec<Board>> {
-    let mut boards = Vec::new();
-    let mut numbers = Vec::new();
-    for line in lines {
+    let mut boards_rows: Vec<Vec<Vec<u8>>> = Vec::new();
+    let mut rows: Vec<Vec<u8>> = Vec::new();
+    for line in lines.iter().chain(std::iter::once(&"")) {
         let row_numbers: Vec<u8> = line
             .split(' ')
             .filter(|item| !item.is_empty())
@@ -84,63 +178,148 @@ fn parse_boards(lines: &[&str]) -> Result<Vec<Board>> {
                 })
             })
             .collect::<Result<Vec<u8>>>()?;
-        match row_numbers.len() {
-            0 => continue,
-            5 => numbers.push(row_numbers),
-            _ => return Err(anyhow!("invalid row number count")),
+        if row_numbers.is_empty() {
+            if !rows.is_empty() {
+                boards_rows.push(std::mem::take(&mut rows));
+            }
+            continue;
         }
+        if rows
+            .first()
+            .is_some_and(|row| row.len() != row_numbers.len())
+        {
+            return Err(anyhow!("invalid row number count"));
+        }
+        rows.push(row_numbers);
     }
 
-    if numbers.is_empty() || numbers.len() % 5 != 0 {
-        return Err(anyhow!("invalid number or rows"));
-    }
+    let size = boards_rows
+        .first()
+        .map(Vec::len)
+        .ok_or_else(|| anyhow!("invalid number or rows"))?;
+    boards_rows
+        .iter()
+        .map(|rows| Board::from_rows(rows, size))
+        .collect()
+}
 
-    for board_numbers in numbers.chunks(5) {
-        let mut grid = [[Cell::default(); 5]; 5];
-        for (row_index, row_numbers) in board_numbers.iter().enumerate() {
-            for (column_index, column_number) in row_numbers.iter().enumerate()
-            {
-                grid[row_index][column_index].number = *column_number;
-            }
-        }
-        boards.push(Board { grid });
-    }
-    Ok(boards)
+/// Reads the draws from the first line and the boards from the rest.
+fn from_lines(lines: &[&str]) -> Result<(Vec<u8>, Vec<Board>)> {
+    let (numbers, boards) = lines
+        .split_first()
+        .ok_or_else(|| anyhow!("missing drawn numbers"))?;
+    Ok((parse_numbers(numbers)?, parse_boards(boards)?))
+}
+
+/// The draws and boards of a JSON bingo input.
+#[derive(Debug, Deserialize)]
+struct JsonInput {
+    draws: Vec<u8>,
+    boards: Vec<Vec<Vec<u8>>>,
+}
+
+/// Reads the draws and boards from a JSON document shaped like
+/// `{"draws": [7, 4], "boards": [[[22, 13, 17, 11, 0], ...], ...]}`.
+fn from_json(text: &str) -> Result<(Vec<u8>, Vec<Board>)> {
+    let input: JsonInput =
+        serde_json::from_str(text).context("failed to parse JSON input")?;
+    let size = input.boards.first().map_or(0, Vec::len);
+    let boards = input
+        .boards
+        .iter()
+        .map(|rows| Board::from_rows(rows, size))
+        .collect::<Result<Vec<_>>>()?;
+    Ok((input.draws, boards))
 }
 
+/// A square board of `size` rows of `size` cells.
 #[derive(Debug)]
 struct Board {
-    grid: [[Cell; 5]; 5],
+    grid: Vec<Vec<Cell>>,
+    size: usize,
+    /// Where each number is on the board, so marking doesn't scan the grid.
+    positions: HashMap<u8, Vec<(usize, usize)>>,
+    /// The first line completed, checked as each number is marked.
+    winning_line: Option<WinLine>,
+    diagonals: bool,
 }
 
 impl Board {
-    pub fn mark_number(&mut self, number: u8) {
-        for row in self.grid.iter_mut() {
-            for cell in row.iter_mut() {
-                if cell.number == number {
-                    cell.marked = true;
-                }
+    fn from_rows(rows: &[Vec<u8>], size: usize) -> Result<Board> {
+        if size == 0
+            || rows.len() != size
+            || rows.iter().any(|row| row.len() != size)
+        {
+            return Err(anyhow!(
+                "boards must be {} rows of {} numbers like the first",
+                size,
+                size
+            ));
+        }
+        let mut positions: HashMap<u8, Vec<(usize, usize)>> = HashMap::new();
+        for (row_index, row) in rows.iter().enumerate() {
+            for (column_index, number) in row.iter().enumerate() {
+                positions
+                    .entry(*number)
+                    .or_default()
+                    .push((row_index, column_index));
             }
         }
+        let grid = rows
+            .iter()
+            .map(|row| row.iter().map(|number| Cell::new(*number)).collect())
+            .collect();
+        Ok(Board {
+            grid,
+            size,
+            positions,
+            winning_line: None,
+            diagonals: false,
+        })
     }
 
-    pub fn is_winner(&self) -> bool {
-        self.grid
-            .iter()
-            .any(|row| row.iter().all(|cell| cell.marked))
-            || self
-                .grid
-                .iter()
-                .fold(vec![0; 5], |mut selected_counts, row| {
-                    row.iter().enumerate().for_each(|(index, cell)| {
-                        if cell.marked {
-                            selected_counts[index] += 1;
-                        }
-                    });
-                    selected_counts
-                })
+    /// Lets the main and anti diagonals win as well as the rows and columns.
+    /// Boards are always square so both diagonals run corner to corner.
+    fn count_diagonals(&mut self) {
+        self.diagonals = true;
+    }
+
+    /// Marks every cell holding the number, then checks only the lines
+    /// through those cells for a completed one.
+    pub fn mark_number(&mut self, number: u8) {
+        let Some(cells) = self.positions.get(&number) else {
+            return;
+        };
+        for &(row, column) in cells {
+            self.grid[row][column].marked = true;
+        }
+        if self.winning_line.is_none() {
+            self.winning_line = cells
                 .iter()
-                .any(|count| *count == 5)
+                .find_map(|&(row, column)| self.completed_line(row, column));
+        }
+    }
+
+    fn completed_line(&self, row: usize, column: usize) -> Option<WinLine> {
+        let lines = [
+            Some(WinLine::Row(row)),
+            Some(WinLine::Column(column)),
+            (self.diagonals && row == column).then_some(WinLine::Diagonal),
+            (self.diagonals && row + column == self.size - 1)
+                .then_some(WinLine::AntiDiagonal),
+        ];
+        lines.into_iter().flatten().find(|line| {
+            line.cells(self.size)
+                .all(|(row, column)| self.grid[row][column].marked)
+        })
+    }
+
+    pub fn is_winner(&self) -> bool {
+        self.winning_line.is_some()
+    }
+
+    pub fn winning_line(&self) -> Option<WinLine> {
+        self.winning_line
     }
 
     pub fn sum_unmarked_numbers(&self) -> i32 {
@@ -154,18 +333,77 @@ impl Board {
             })
         })
     }
+
+    /// Renders the board a row per line. Marking only leaves the marked
+    /// numbers visible with the unmarked cells blanked.
+    /// The winning line, if any, is shown in green.
+    fn render(&self, marked_only: bool) -> String {
+        let on_winning_line = |row, column| {
+            self.winning_line()
+                .is_some_and(|line| line.contains(row, column, self.size))
+        };
+        self.grid
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                let cells = row
+                    .iter()
+                    .enumerate()
+                    .map(|(column_index, cell)| {
+                        match (marked_only, cell.marked) {
+                            (true, false) => "  ".to_owned(),
+                            _ if on_winning_line(row_index, column_index) => {
+                                format!(
+                                    "{:>2}",
+                                    cell.number.to_string().green().bold()
+                                )
+                            }
+                            (false, _) => cell.to_string(),
+                            (true, true) => format!("{:>2}", cell.number),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                format!("{}\n", cells.join(" "))
+            })
+            .collect()
+    }
 }
 
 impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.grid {
-            writeln!(
-                f,
-                "{} {} {} {} {}",
-                row[0], row[1], row[2], row[3], row[4],
-            )?;
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// A completed row, column or diagonal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinLine {
+    Row(usize),
+    Column(usize),
+    /// From the top left to the bottom right.
+    Diagonal,
+    /// From the top right to the bottom left.
+    AntiDiagonal,
+}
+
+impl WinLine {
+    /// The positions along the line on a board of `size`.
+    fn cells(self, size: usize) -> impl Iterator<Item = (usize, usize)> {
+        (0..size).map(move |index| match self {
+            WinLine::Row(row) => (row, index),
+            WinLine::Column(column) => (index, column),
+            WinLine::Diagonal => (index, index),
+            WinLine::AntiDiagonal => (index, size - 1 - index),
+        })
+    }
+
+    fn contains(&self, row: usize, column: usize, size: usize) -> bool {
+        match self {
+            WinLine::Row(line_row) => row == *line_row,
+            WinLine::Column(line_column) => column == *line_column,
+            WinLine::Diagonal => row == column,
+            WinLine::AntiDiagonal => row + column + 1 == size,
         }
-        Ok(())
     }
 }
 
@@ -176,7 +414,6 @@ pub struct Cell {
 }
 
 impl Cell {
-    #[allow(dead_code)]
     pub fn new(number: u8) -> Cell {
         Cell {
             number,
@@ -195,9 +432,180 @@ impl std::fmt::Display for Cell {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
 #[cfg(test)]
 mod tests {
-    use super::{Board, Cell};
+    use super::{
+        from_json, from_lines, losers, parse_boards, parse_numbers, play,
+        BingoResult, Board, WinLine, SAMPLE,
+    };
+
+    #[test]
+    fn parse_numbers_parses_comma_separated_numbers() {
+        let numbers = parse_numbers("7,4,9,5,11").expect("valid input");
+
+        assert_eq!(numbers, vec![7, 4, 9, 5, 11]);
+    }
+
+    #[test]
+    fn parse_numbers_fails_on_non_numeric_entry() {
+        assert!(parse_numbers("7,four,9").is_err());
+    }
+
+    #[test]
+    fn parse_boards_parses_boards() {
+        let lines = BOARDS.split('\n').collect::<Vec<&str>>();
+
+        let boards = parse_boards(&lines).expect("valid input");
+
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[0].grid[0][0].number, 22);
+        assert_eq!(boards[0].grid[4][4].number, 19);
+        assert_eq!(boards[1].grid[0][0].number, 3);
+        assert_eq!(boards[1].grid[4][4].number, 6);
+    }
+
+    #[test]
+    fn parse_boards_fails_on_short_row() {
+        let lines = ["22 13 17 11  0", " 8  2 23  4"];
+
+        let err = parse_boards(&lines).expect_err("invalid input");
+
+        assert_eq!(err.to_string(), "invalid row number count");
+    }
+
+    #[test]
+    fn parse_boards_infers_the_board_size() {
+        let lines = ["1 2 3", "4 5 6", "7 8 9", "", "9 8 7", "6 5 4", "3 2 1"];
+
+        let mut boards = parse_boards(&lines).expect("valid input");
+
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[1].size, 3);
+        [2u8, 5, 8]
+            .iter()
+            .for_each(|number| boards[0].mark_number(*number));
+        assert!(boards[0].is_winner());
+        assert_eq!(boards[0].sum_unmarked_numbers(), 30);
+        assert!(!boards[1].is_winner());
+        assert!(parse_boards(&["1 2 3", "4 5 6", "7 8 9", "", "1 2", "3 4"])
+            .is_err());
+        assert!(parse_boards(&["1 2 3", "4 5 6"]).is_err());
+    }
+
+    #[test]
+    fn from_json_plays_like_the_text_format() {
+        let (draws, mut boards) = from_json(JSON).expect("valid input");
+
+        assert_eq!(draws.len(), 27);
+        assert_eq!(boards.len(), 3);
+        let score = draws.iter().find_map(|number| {
+            boards
+                .iter_mut()
+                .for_each(|board| board.mark_number(*number));
+            boards
+                .iter()
+                .find(|board| board.is_winner())
+                .map(|board| board.sum_unmarked_numbers() * *number as i32)
+        });
+        assert_eq!(score, Some(4512));
+    }
+
+    #[test]
+    fn from_json_fails_on_short_board() {
+        let err = from_json(r#"{"draws": [1], "boards": [[[1, 2]]]}"#)
+            .expect_err("invalid input");
+
+        assert_eq!(
+            err.to_string(),
+            "boards must be 1 rows of 1 numbers like the first"
+        );
+        assert!(from_json(r#"{"boards": []}"#).is_err());
+        assert!(from_json(r#"{"draws": [256], "boards": []}"#).is_err());
+        assert!(from_json(r#"{"draws": [-1], "boards": []}"#).is_err());
+    }
+
+    #[test]
+    fn from_json_ignores_other_fields() {
+        let (draws, boards) = from_json(
+            r#"{"name": "a \"tiny\" game", "seed": -1.5e3, "draws": [7],
+                "boards": [[[7]]], "shuffled": false}"#,
+        )
+        .expect("valid input");
+
+        assert_eq!(draws, [7]);
+        assert_eq!(boards.len(), 1);
+    }
+
+    #[test]
+    fn play_finds_the_first_and_last_winners() {
+        let lines = SAMPLE.lines().collect::<Vec<&str>>();
+
+        let (numbers, mut boards) = from_lines(&lines).expect("valid input");
+        assert_eq!(
+            play(&numbers, &mut boards, false),
+            Some(BingoResult {
+                board_index: 2,
+                winning_number: 24,
+                turns: 12,
+                sum_unmarked: 188,
+                score: 4512,
+            })
+        );
+
+        let (numbers, mut boards) = from_lines(&lines).expect("valid input");
+        assert_eq!(
+            play(&numbers, &mut boards, true),
+            Some(BingoResult {
+                board_index: 1,
+                winning_number: 13,
+                turns: 15,
+                sum_unmarked: 148,
+                score: 1924,
+            })
+        );
+
+        let (numbers, mut boards) = from_lines(&lines).expect("valid input");
+        assert_eq!(play(&numbers[..4], &mut boards, false), None);
+    }
+
+    #[test]
+    fn losers_are_the_boards_that_never_win() {
+        let lines = [
+            "1,2,3,4", "", "1 2", "3 9", "", "5 6", "7 8", "", "4 9", "3 8",
+        ];
+        let (numbers, mut boards) = from_lines(&lines).expect("valid input");
+
+        let result = play(&numbers, &mut boards, false).expect("a winner");
+
+        assert_eq!((result.board_index, result.turns), (0, 2));
+        assert_eq!(losers(&numbers, &mut boards), vec![1]);
+
+        let lines = SAMPLE.lines().collect::<Vec<&str>>();
+        let (numbers, mut boards) = from_lines(&lines).expect("valid input");
+        assert!(losers(&numbers, &mut boards).is_empty());
+    }
 
     #[test]
     fn is_winner_is_true_when_all_cells_of_any_column_are_selected() {
@@ -221,6 +629,101 @@ mod tests {
         assert!(board.is_winner());
     }
 
+    #[test]
+    fn is_winner_counts_diagonals_only_when_asked() {
+        let mut board = create_board();
+        let mut diagonal_board = create_board();
+        diagonal_board.count_diagonals();
+
+        [0u8, 6, 12, 18, 24].iter().for_each(|number| {
+            board.mark_number(*number);
+            diagonal_board.mark_number(*number);
+        });
+
+        assert!(!board.is_winner());
+        assert!(diagonal_board.is_winner());
+
+        let mut anti_diagonal_board = create_board();
+        anti_diagonal_board.count_diagonals();
+        [4u8, 8, 12, 16]
+            .iter()
+            .for_each(|number| anti_diagonal_board.mark_number(*number));
+        assert!(!anti_diagonal_board.is_winner());
+        anti_diagonal_board.mark_number(20);
+        assert!(anti_diagonal_board.is_winner());
+        assert_eq!(
+            anti_diagonal_board.winning_line(),
+            Some(WinLine::AntiDiagonal)
+        );
+        assert_eq!(diagonal_board.winning_line(), Some(WinLine::Diagonal));
+    }
+
+    #[test]
+    fn winning_line_is_the_first_completed() {
+        let mut board = create_board();
+
+        [1u8, 6, 11, 16]
+            .iter()
+            .for_each(|number| board.mark_number(*number));
+        assert_eq!(board.winning_line(), None);
+        board.mark_number(21);
+        assert_eq!(board.winning_line(), Some(WinLine::Column(1)));
+        [20u8, 22, 23, 24]
+            .iter()
+            .for_each(|number| board.mark_number(*number));
+        assert_eq!(board.winning_line(), Some(WinLine::Column(1)));
+        assert!(WinLine::Row(4).contains(4, 0, 5));
+        assert_eq!(
+            WinLine::AntiDiagonal.cells(3).collect::<Vec<_>>(),
+            [(0, 2), (1, 1), (2, 0)]
+        );
+        assert!(!WinLine::AntiDiagonal.contains(0, 0, 5));
+    }
+
+    #[test]
+    fn render_marked_only_shows_only_marked_numbers() {
+        let mut board = create_board();
+
+        vec![5u8, 6u8, 7u8, 8u8, 13u8]
+            .into_iter()
+            .for_each(|number| board.mark_number(number));
+        let render = board.render(true);
+
+        assert!(!board.is_winner());
+        assert_eq!(
+            render.split_whitespace().collect::<Vec<_>>(),
+            vec!["5", "6", "7", "8", "13"]
+        );
+        assert_eq!(render.lines().nth(1), Some(" 5  6  7  8   "));
+    }
+
+    #[test]
+    fn mark_number_uses_the_board_positions() {
+        let mut board = create_board();
+
+        board.mark_number(99);
+        board.mark_number(12);
+
+        assert_eq!(board.positions.len(), 25);
+        assert!(board.grid[2][2].marked);
+        assert_eq!(board.sum_unmarked_numbers(), 288);
+        assert!(!board.is_winner());
+    }
+
+    #[test]
+    fn mark_number_marks_every_cell_with_a_repeated_number() {
+        let mut board = Board::from_rows(&[vec![1, 2], vec![2, 3]], 2)
+            .expect("valid board");
+
+        board.mark_number(2);
+
+        assert!(board.grid[0][1].marked && board.grid[1][0].marked);
+        assert_eq!(board.sum_unmarked_numbers(), 4);
+        assert!(!board.is_winner());
+        board.mark_number(3);
+        assert_eq!(board.winning_line(), Some(WinLine::Row(1)));
+    }
+
     #[test]
     fn is_winner_is_false_when_no_cell_is_selected() {
         let board = create_board();
@@ -229,44 +732,52 @@ mod tests {
     }
 
     fn create_board() -> Board {
-        Board {
-            grid: [
-                [
-                    Cell::new(0),
-                    Cell::new(1),
-                    Cell::new(2),
-                    Cell::new(3),
-                    Cell::new(4),
-                ],
-                [
-                    Cell::new(5),
-                    Cell::new(6),
-                    Cell::new(7),
-                    Cell::new(8),
-                    Cell::new(9),
-                ],
-                [
-                    Cell::new(10),
-                    Cell::new(11),
-                    Cell::new(12),
-                    Cell::new(13),
-                    Cell::new(14),
-                ],
-                [
-                    Cell::new(15),
-                    Cell::new(16),
-                    Cell::new(17),
-                    Cell::new(18),
-                    Cell::new(19),
-                ],
-                [
-                    Cell::new(20),
-                    Cell::new(21),
-                    Cell::new(22),
-                    Cell::new(23),
-                    Cell::new(24),
-                ],
-            ],
-        }
+        let rows = (0..5u8)
+            .map(|row| (0..5u8).map(|col| row * 5 + col).collect())
+            .collect::<Vec<Vec<u8>>>();
+        Board::from_rows(&rows, 5).expect("valid board")
     }
+
+    const JSON: &str = r#"{
+  "draws": [
+    7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22,
+    18, 20, 8, 19, 3, 26, 1
+  ],
+  "boards": [
+    [
+      [22, 13, 17, 11, 0],
+      [8, 2, 23, 4, 24],
+      [21, 9, 14, 16, 7],
+      [6, 10, 3, 18, 5],
+      [1, 12, 20, 15, 19]
+    ],
+    [
+      [3, 15, 0, 2, 22],
+      [9, 18, 13, 17, 5],
+      [19, 8, 7, 25, 23],
+      [20, 11, 10, 24, 4],
+      [14, 21, 16, 12, 6]
+    ],
+    [
+      [14, 21, 17, 24, 4],
+      [10, 16, 15, 9, 19],
+      [18, 8, 23, 26, 20],
+      [22, 11, 13, 6, 5],
+      [2, 0, 12, 3, 7]
+    ]
+  ]
+}"#;
+
+    const BOARDS: &str = r"
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6";
 }
@@ -1,24 +1,48 @@
-use std::{cmp::Ordering, collections::HashMap, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 use regex::Regex;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::Input;
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(long)]
     steps: usize,
+
+    #[structopt(long)]
+    rank: Option<usize>,
+
+    #[structopt(long)]
+    introduced: bool,
 }
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
         let mut polymizer = Polymerizer::parse(
-            read_lines(&self.input)?.iter().map(String::as_str),
+            self.input.lines(SAMPLE)?.iter().map(String::as_str),
         )?;
+        if self.introduced {
+            let mut introduced = polymizer
+                .introduced_elements()
+                .into_iter()
+                .collect::<Vec<_>>();
+            introduced.sort_unstable();
+            if introduced.is_empty() {
+                println!("No elements are introduced only by insertion");
+            } else {
+                println!(
+                    "Elements introduced only by insertion: {}",
+                    introduced.iter().collect::<String>()
+                );
+            }
+        }
         for _ in 0..self.steps {
             polymizer.step();
         }
@@ -32,6 +56,14 @@ impl Command {
                 .join("\n")
         );
         println!("  Difference: {}", counts[0].1 - counts[counts.len() - 1].1);
+        if let Some(rank) = self.rank {
+            match polymizer.nth_most_common(rank) {
+                Some((element, count)) => {
+                    println!("  Rank {}: [{}; {}]", rank, element, count)
+                }
+                None => println!("  Rank {}: no element", rank),
+            }
+        }
         Ok(())
     }
 }
@@ -47,8 +79,8 @@ impl ParsePolymerizerError {
 
 fn element_pair_counts(chars: &[char]) -> HashMap<ElementPair, usize> {
     let mut counts: HashMap<ElementPair, usize> = HashMap::new();
-    for index in 0..(chars.len() - 1) {
-        let pair = ElementPair::new(chars[index], chars[index + 1]);
+    for pair in chars.windows(2) {
+        let pair = ElementPair::new(pair[0], pair[1]);
         *counts.entry(pair).or_insert(0) += 1;
     }
 
@@ -71,6 +103,7 @@ impl ElementPair {
 
 #[derive(Debug)]
 struct Polymerizer {
+    template: String,
     insertions: HashMap<ElementPair, Element>,
     last_char: char,
     element_pair_counts: HashMap<ElementPair, usize>,
@@ -83,7 +116,7 @@ impl Polymerizer {
     where
         Iter: Iterator<Item = &'iter str>,
     {
-        let insertion_regex = Regex::new("([A-Z]{2}) -> ([A-Z])")
+        let insertion_regex = Regex::new(r"([A-Z]{2})\s*->\s*([A-Z])")
             .map_err(|_| ParsePolymerizerError::new("regex"))?;
         let mut template = None;
         let mut insertions: HashMap<ElementPair, Element> = HashMap::new();
@@ -124,25 +157,30 @@ impl Polymerizer {
         }
 
         let template = template.expect("is some");
-        let template_chars =
-            template.chars().into_iter().collect::<Vec<char>>();
+        let template_chars = template.chars().collect::<Vec<char>>();
         let last_char = template_chars[template_chars.len() - 1];
         let element_pair_counts = element_pair_counts(&template_chars);
         Ok(Polymerizer {
+            template,
             insertions,
             last_char,
             element_pair_counts,
         })
     }
 
+    /// Applies every rule at once. A pair without a rule, such as one
+    /// formed with an introduced element, is carried over unchanged.
     fn step(&mut self) {
         let mut pair_counts: HashMap<ElementPair, usize> = HashMap::new();
         for (pair, count) in self.element_pair_counts.iter() {
-            let insertion = self.insertions[pair];
-            let first_pair = ElementPair::new(pair.first, insertion);
-            *pair_counts.entry(first_pair).or_insert(0) += count;
-            let second_pair = ElementPair::new(insertion, pair.second);
-            *pair_counts.entry(second_pair).or_insert(0) += count;
+            if let Some(&insertion) = self.insertions.get(pair) {
+                let first_pair = ElementPair::new(pair.first, insertion);
+                *pair_counts.entry(first_pair).or_insert(0) += count;
+                let second_pair = ElementPair::new(insertion, pair.second);
+                *pair_counts.entry(second_pair).or_insert(0) += count;
+            } else {
+                *pair_counts.entry(*pair).or_insert(0) += count;
+            }
         }
         self.element_pair_counts = pair_counts;
     }
@@ -167,18 +205,62 @@ impl Polymerizer {
         });
         counts.into_iter()
     }
+
+    /// Returns the elements inserted by a rule that are neither in the
+    /// template nor in the pair of any rule, so only insertion brings them
+    /// into the polymer.
+    fn introduced_elements(&self) -> HashSet<char> {
+        let present = self
+            .insertions
+            .keys()
+            .flat_map(|pair| [pair.first, pair.second])
+            .chain(self.template.chars())
+            .collect::<HashSet<_>>();
+        self.insertions
+            .values()
+            .copied()
+            .filter(|element| !present.contains(element))
+            .collect()
+    }
+
+    /// Returns the element ranked `n`, counting from 0 for the most common.
+    fn nth_most_common(&self, n: usize) -> Option<(char, usize)> {
+        self.element_counts().nth(n)
+    }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"NNCB
+
+    CH -> B
+    HH -> N
+    CB -> H
+    NH -> C
+    HB -> C
+    HC -> B
+    HN -> C
+    NN -> C
+    BH -> H
+    NC -> B
+    NB -> B
+    BN -> B
+    BB -> N
+    BC -> B
+    CC -> N
+    CN -> C";
+
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, HashSet};
+
     use crate::day::fourteen::ElementPair;
 
-    use super::Polymerizer;
+    use super::{Polymerizer, SAMPLE};
 
     #[test]
     fn polymerizer_parse() {
         let polymerizer =
-            Polymerizer::parse(INPUT.split('\n')).expect("valid input");
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid input");
 
         assert_eq!(polymerizer.insertions[&ElementPair::new('C', 'H')], 'B');
         assert_eq!(polymerizer.insertions[&ElementPair::new('B', 'H')], 'H');
@@ -192,7 +274,7 @@ mod tests {
     #[test]
     fn polymerizer_step() {
         let mut polymerizer =
-            Polymerizer::parse(INPUT.split('\n')).expect("valid input");
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid input");
 
         polymerizer.step();
         assert_eq!(
@@ -204,7 +286,7 @@ mod tests {
     #[test]
     fn polymerizer_four_steps() {
         let mut polymerizer =
-            Polymerizer::parse(INPUT.split('\n')).expect("valid inputg");
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid inputg");
 
         (0..4).for_each(|_| polymerizer.step());
 
@@ -217,7 +299,7 @@ mod tests {
     #[test]
     fn polymerizer_ten_steps() {
         let mut polymerizer =
-            Polymerizer::parse(INPUT.split('\n')).expect("valid input");
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid input");
 
         (0..10).for_each(|_| polymerizer.step());
 
@@ -227,22 +309,84 @@ mod tests {
         );
     }
 
-    const INPUT: &str = r"NNCB
+    #[test]
+    fn polymerizer_nth_most_common() {
+        let mut polymerizer =
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid input");
 
-    CH -> B
-    HH -> N
-    CB -> H
-    NH -> C
-    HB -> C
-    HC -> B
-    HN -> C
-    NN -> C
-    BH -> H
-    NC -> B
-    NB -> B
-    BN -> B
-    BB -> N
-    BC -> B
-    CC -> N
-    CN -> C";
+        (0..10).for_each(|_| polymerizer.step());
+
+        assert_eq!(polymerizer.nth_most_common(0), Some(('B', 1749)));
+        assert_eq!(polymerizer.nth_most_common(3), Some(('H', 161)));
+        assert_eq!(polymerizer.nth_most_common(4), None);
+    }
+
+    #[test]
+    fn polymerizer_introduced_elements() {
+        let polymerizer =
+            Polymerizer::parse(SAMPLE.split('\n')).expect("valid input");
+        let introducing_polymerizer =
+            Polymerizer::parse(["NNCB", "", "NN -> C", "NC -> Z"].into_iter())
+                .expect("valid input");
+
+        assert!(polymerizer.introduced_elements().is_empty());
+        assert_eq!(
+            introducing_polymerizer.introduced_elements(),
+            HashSet::from(['Z'])
+        );
+
+        // B is in the template but in no rule's pair
+        let template_polymerizer =
+            Polymerizer::parse(["NNB", "", "NN -> B", "NC -> Z"].into_iter())
+                .expect("valid input");
+        assert_eq!(
+            template_polymerizer.introduced_elements(),
+            HashSet::from(['Z'])
+        );
+    }
+
+    #[test]
+    fn polymerizer_steps_past_pairs_without_rules() {
+        let mut polymerizer =
+            Polymerizer::parse(["NNCB", "", "NN -> C", "NC -> Z"].into_iter())
+                .expect("valid input");
+
+        // NNCB becomes NCNZCB and then NZCNZCB
+        polymerizer.step();
+        polymerizer.step();
+
+        assert_eq!(
+            polymerizer.element_counts().collect::<HashMap<_, _>>(),
+            HashMap::from([('N', 2), ('Z', 2), ('C', 2), ('B', 1)])
+        );
+    }
+
+    #[test]
+    fn polymerizer_parse_flexible_whitespace() {
+        let polymerizer = Polymerizer::parse(
+            ["NNCB", "", "CH  ->  B", "NC\t->\tH"].into_iter(),
+        )
+        .expect("valid input");
+
+        assert_eq!(polymerizer.insertions[&ElementPair::new('C', 'H')], 'B');
+        assert_eq!(polymerizer.insertions[&ElementPair::new('N', 'C')], 'H');
+    }
+
+    #[test]
+    fn polymerizer_single_element_template() {
+        let mut polymerizer =
+            Polymerizer::parse(["A", "", "BC -> D"].into_iter())
+                .expect("valid input");
+
+        assert_eq!(
+            polymerizer.element_counts().collect::<Vec<_>>(),
+            vec![('A', 1)]
+        );
+
+        polymerizer.step();
+        assert_eq!(
+            polymerizer.element_counts().collect::<Vec<_>>(),
+            vec![('A', 1)]
+        );
+    }
 }
@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+/// A point on a plane. Points order by `x` then by `y`.
+#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Display> Display for Point<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Point;
+
+    #[test]
+    fn point_ord() {
+        let mut points = vec![
+            Point { x: 2, y: 0 },
+            Point { x: 1, y: 5 },
+            Point { x: 1, y: 2 },
+            Point { x: 1, y: 2 },
+        ];
+        points.sort();
+        points.dedup();
+
+        assert_eq!(
+            points,
+            [
+                Point { x: 1, y: 2 },
+                Point { x: 1, y: 5 },
+                Point { x: 2, y: 0 }
+            ]
+        );
+    }
+
+    #[test]
+    fn point_display() {
+        assert_eq!(Point { x: -3, y: 4 }.to_string(), "(-3, 4)");
+    }
+}
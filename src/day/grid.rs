@@ -0,0 +1,141 @@
+use std::ops::{Index, IndexMut};
+
+const NEIGHBORS4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const NEIGHBORS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells addressed by `(row, col)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Grid<T> {
+    rows: Vec<Vec<T>>,
+}
+
+impl<T> Grid<T> {
+    /// Wraps `rows` that are expected to share the same width.
+    pub fn new(rows: Vec<Vec<T>>) -> Self {
+        Grid { rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[Vec<T>] {
+        &self.rows
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.rows.get(row)?.get(col)
+    }
+
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rows.iter_mut().flatten()
+    }
+
+    /// Every position in the grid, a row at a time.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width();
+        (0..self.height())
+            .flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// The positions above, left, right and below `(row, col)` that are
+    /// within the grid.
+    pub fn neighbors4(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbors(row, col, &NEIGHBORS4)
+    }
+
+    /// The positions surrounding `(row, col)`, diagonals included, that are
+    /// within the grid.
+    pub fn neighbors8(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbors(row, col, &NEIGHBORS8)
+    }
+
+    fn neighbors(
+        &self,
+        row: usize,
+        col: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (height, width) = (self.height(), self.width());
+        offsets.iter().filter_map(move |(row_offset, col_offset)| {
+            let row = row.checked_add_signed(*row_offset)?;
+            let col = col.checked_add_signed(*col_offset)?;
+            (row < height && col < width).then_some((row, col))
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.rows[row][col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self.rows[row][col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+
+    #[test]
+    fn grid_dimensions() {
+        let grid = Grid::new(vec![vec![1u8, 2, 3], vec![4, 5, 6]]);
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 2), Some(&6));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid[(0, 1)], 2);
+        assert_eq!(Grid::<u8>::default().width(), 0);
+    }
+
+    #[test]
+    fn grid_neighbors4() {
+        let grid = Grid::new(vec![vec![0u8; 3]; 3]);
+
+        assert_eq!(grid.neighbors4(0, 0).collect::<Vec<_>>(), [(0, 1), (1, 0)]);
+        assert_eq!(
+            grid.neighbors4(1, 1).collect::<Vec<_>>(),
+            [(0, 1), (1, 0), (1, 2), (2, 1)]
+        );
+        assert_eq!(grid.neighbors4(2, 2).collect::<Vec<_>>(), [(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn grid_neighbors8() {
+        let grid = Grid::new(vec![vec![0u8; 3]; 3]);
+
+        assert_eq!(
+            grid.neighbors8(0, 2).collect::<Vec<_>>(),
+            [(0, 1), (1, 1), (1, 2)]
+        );
+        assert_eq!(grid.neighbors8(1, 1).count(), 8);
+        assert_eq!(grid.neighbors8(2, 1).count(), 5);
+    }
+}
@@ -1,10 +1,15 @@
 use std::{
+    ffi::OsStr,
+    fmt::Display,
     fs::File,
-    io::{BufRead, BufReader, Read},
-    path::Path,
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use structopt::{self, StructOpt};
 
 pub mod eight;
 pub mod eleven;
@@ -12,6 +17,9 @@ pub mod fifteen;
 pub mod five;
 pub mod four;
 pub mod fourteen;
+mod geometry;
+mod grid;
+mod mode;
 pub mod nine;
 pub mod one;
 pub mod seven;
@@ -24,25 +32,428 @@ pub mod three;
 pub mod twelve;
 pub mod two;
 
-fn read_lines(file_path: &Path) -> Result<Vec<String>> {
-    let file = File::open(file_path).with_context(|| {
-        format!("failed to open file '{}'", file_path.display())
-    })?;
-    let mut lines = Vec::new();
-    let reader = BufReader::new(file);
-    for line in reader.lines() {
-        lines.push(line.with_context(|| {
-            format!("failed to read line from '{}'", file_path.display())
-        })?);
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to parse format from '{0}'")]
+pub struct ParseFormatError(String);
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmptyInputError {
+    #[error("input file '{0}' is empty")]
+    File(String),
+    /// The `--sample` or `--inline` text rather than a file.
+    #[error("{0} input is empty")]
+    Given(&'static str),
+}
+
+/// How a command reports its answers: prose for people or JSON for scripts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(ParseFormatError(s.to_owned())),
+        }
+    }
+}
+
+/// The answers to both parts of a day's puzzle, worked out from the text of
+/// its input.
+pub trait Day {
+    fn part_one(&self, input: &str) -> Result<String>;
+    fn part_two(&self, input: &str) -> Result<String>;
+}
+
+/// The two bytes every gzip file starts with.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The UTF-8 byte order mark some editors write at the start of a file.
+const BOM: &[u8] = "\u{feff}".as_bytes();
+
+/// Where a day reads its puzzle input from: a file, stdin for `-`, the text
+/// given with `--inline`, or the example from the puzzle description when
+/// `--sample` is set.
+#[derive(Debug, Default, StructOpt)]
+pub struct Input {
+    #[structopt(
+        required_unless_one(&["sample", "inline"]),
+        parse(from_os_str)
+    )]
+    input: Option<PathBuf>,
+
+    /// Use the puzzle's example input instead of a file
+    #[structopt(long, conflicts_with("input"))]
+    sample: bool,
+
+    /// The puzzle input itself, instead of a file
+    #[structopt(long, conflicts_with_all(&["input", "sample"]))]
+    inline: Option<String>,
+}
+
+impl Input {
+    fn path(&self) -> Result<&Path> {
+        self.input
+            .as_deref()
+            .ok_or_else(|| anyhow!("no input file was given"))
+    }
+
+    fn reader(&self, sample: &'static str) -> Result<Box<dyn BufRead>> {
+        match given_text(self.sample, &self.inline, sample) {
+            Some(text) => Ok(Box::new(Cursor::new(text.to_owned()))),
+            None => open_reader(self.path()?),
+        }
+    }
+
+    fn lines(&self, sample: &'static str) -> Result<Vec<String>> {
+        self.lines_iter(sample)?.collect()
+    }
+
+    fn lines_iter(
+        &self,
+        sample: &'static str,
+    ) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        match given_text(self.sample, &self.inline, sample) {
+            Some(text) => Ok(given_lines(text)),
+            None => Ok(Box::new(read_lines_iter(self.path()?)?)),
+        }
+    }
+
+    fn text(&self, sample: &'static str) -> Result<String> {
+        match given_text(self.sample, &self.inline, sample) {
+            Some(text) => Ok(text.to_owned()),
+            None => read_all_text(self.path()?),
+        }
+    }
+
+    /// Like `text` but fails when there's nothing besides whitespace, for
+    /// days that need at least one row to work with.
+    fn non_empty_text(&self, sample: &'static str) -> Result<String> {
+        let text = self.text(sample)?;
+        if text.trim().is_empty() {
+            return Err(self.empty_input_error().into());
+        }
+        Ok(text)
+    }
+
+    /// Like `reader` but fails when there's nothing besides whitespace.
+    /// Leading blank lines are skipped to find out, so the rest can still
+    /// be streamed.
+    fn non_empty_reader(
+        &self,
+        sample: &'static str,
+    ) -> Result<Box<dyn BufRead>> {
+        let mut reader = self.reader(sample)?;
+        loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Err(self.empty_input_error().into());
+            }
+            match buffer.iter().position(|byte| !byte.is_ascii_whitespace()) {
+                Some(start) => {
+                    let blank_lines = buffer[..start]
+                        .iter()
+                        .rposition(|byte| *byte == b'\n')
+                        .map_or(0, |end| end + 1);
+                    reader.consume(blank_lines);
+                    return Ok(reader);
+                }
+                None => {
+                    let length = buffer.len();
+                    reader.consume(length);
+                }
+            }
+        }
+    }
+
+    fn empty_input_error(&self) -> EmptyInputError {
+        if self.sample {
+            EmptyInputError::Given("sample")
+        } else if self.inline.is_some() {
+            EmptyInputError::Given("inline")
+        } else {
+            EmptyInputError::File(self.to_string())
+        }
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.input, self.sample, &self.inline) {
+            (_, true, _) => write!(f, "sample"),
+            (_, false, Some(_)) => write!(f, "inline"),
+            (Some(path), false, None) => write!(f, "{}", path.display()),
+            (None, false, None) => write!(f, "no input"),
+        }
+    }
+}
+
+/// Like [`Input`] but takes any number of files, read one after another as a
+/// single stream of lines.
+#[derive(Debug, Default, StructOpt)]
+pub struct Inputs {
+    #[structopt(
+        required_unless_one(&["sample", "inline"]),
+        parse(from_os_str)
+    )]
+    inputs: Vec<PathBuf>,
+
+    /// Use the puzzle's example input instead of files
+    #[structopt(long, conflicts_with("inputs"))]
+    sample: bool,
+
+    /// The puzzle input itself, instead of files
+    #[structopt(long, conflicts_with_all(&["inputs", "sample"]))]
+    inline: Option<String>,
+}
+
+impl Inputs {
+    fn lines(&self, sample: &'static str) -> Result<Vec<String>> {
+        self.lines_iter(sample)?.collect()
+    }
+
+    fn lines_iter(
+        &self,
+        sample: &'static str,
+    ) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        match given_text(self.sample, &self.inline, sample) {
+            Some(text) => Ok(given_lines(text)),
+            None => Ok(Box::new(read_inputs_lines_iter(&self.inputs)?)),
+        }
+    }
+}
+
+/// The input text when it comes from the command line rather than a file:
+/// the `sample` when it was asked for, otherwise any `--inline` text.
+fn given_text<'a>(
+    use_sample: bool,
+    inline: &'a Option<String>,
+    sample: &'static str,
+) -> Option<&'a str> {
+    if use_sample {
+        Some(sample)
+    } else {
+        inline.as_deref()
+    }
+}
+
+fn given_lines(text: &str) -> Box<dyn Iterator<Item = Result<String>>> {
+    let lines = text.lines().map(str::to_owned).collect::<Vec<_>>();
+    Box::new(lines.into_iter().map(Ok))
+}
+
+/// The path `-`, or an empty path, reads the input from stdin.
+fn is_stdin(file_path: &Path) -> bool {
+    file_path.as_os_str().is_empty() || file_path.as_os_str() == "-"
+}
+
+/// Opens the input, decompressing it first when it has a `.gz` extension or
+/// starts with the gzip magic bytes, and skips any byte order mark.
+fn open_reader(file_path: &Path) -> Result<Box<dyn BufRead>> {
+    let mut reader: Box<dyn BufRead> = if is_stdin(file_path) {
+        Box::new(io::stdin().lock())
+    } else {
+        let file = File::open(file_path).with_context(|| {
+            format!("failed to open file '{}'", file_path.display())
+        })?;
+        Box::new(BufReader::new(file))
+    };
+    let compressed = file_path.extension() == Some(OsStr::new("gz"))
+        || reader
+            .fill_buf()
+            .with_context(|| {
+                format!("failed to read from '{}'", file_path.display())
+            })?
+            .starts_with(GZIP_MAGIC);
+    if compressed {
+        reader = Box::new(BufReader::new(GzDecoder::new(reader)));
     }
-    Ok(lines)
+    let bom = reader
+        .fill_buf()
+        .with_context(|| {
+            format!("failed to read from '{}'", file_path.display())
+        })?
+        .starts_with(BOM);
+    if bom {
+        reader.consume(BOM.len());
+    }
+    Ok(reader)
+}
+
+fn read_lines(file_path: &Path) -> Result<Vec<String>> {
+    read_lines_iter(file_path)?.collect()
+}
+
+/// Reads the lines lazily for callers that only pass over them once,
+/// dropping any trailing `\r`.
+fn read_lines_iter(
+    file_path: &Path,
+) -> Result<impl Iterator<Item = Result<String>>> {
+    let display = file_path.display().to_string();
+    Ok(open_reader(file_path)?.lines().map(move |line| {
+        let mut line = line.with_context(|| {
+            format!("failed to read line from '{}'", display)
+        })?;
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        Ok(line)
+    }))
 }
 
 fn read_all_text(file_path: &Path) -> Result<String> {
-    let mut file = File::open(file_path).with_context(|| {
-        format!("failed to open file '{}'", file_path.display())
-    })?;
+    let mut reader = open_reader(file_path)?;
     let mut buffer = String::new();
-    let _size = file.read_to_string(&mut buffer)?;
+    let _size = reader.read_to_string(&mut buffer)?;
     Ok(buffer)
 }
+
+/// Opens every file up front so a missing one fails before any are read,
+/// then yields their lines in order.
+fn read_inputs_lines_iter(
+    file_paths: &[PathBuf],
+) -> Result<impl Iterator<Item = Result<String>>> {
+    let iters = file_paths
+        .iter()
+        .map(|file_path| read_lines_iter(file_path))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(iters.into_iter().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Read,
+        path::{Path, PathBuf},
+    };
+
+    use super::{
+        is_stdin, read_all_text, read_inputs_lines_iter, read_lines,
+        read_lines_iter, Format, Input,
+    };
+
+    #[test]
+    fn is_stdin_test() {
+        assert!(is_stdin(Path::new("-")));
+        assert!(is_stdin(Path::new("")));
+        assert!(!is_stdin(Path::new("input.txt")));
+        assert!(!is_stdin(Path::new("./-")));
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert_eq!("text".parse::<Format>().expect("valid"), Format::Text);
+        assert_eq!("json".parse::<Format>().expect("valid"), Format::Json);
+        assert!("yaml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn read_lines_iter_test() {
+        let path = Path::new("data/two.input");
+        let mut lines = read_lines_iter(path).expect("readable file");
+
+        assert_eq!(
+            lines.next().map(|line| line.expect("valid line")),
+            read_lines(path).expect("readable file").into_iter().next()
+        );
+        assert!(read_lines_iter(Path::new("missing.input")).is_err());
+    }
+
+    #[test]
+    fn read_crlf_input_with_bom() {
+        let path = Path::new("data/fixtures/two.sample.crlf");
+
+        assert_eq!(
+            read_lines(path).expect("readable file"),
+            read_lines(Path::new("data/fixtures/two.sample.gz"))
+                .expect("valid gzip")
+        );
+        let text = read_all_text(path).expect("readable file");
+        assert!(text.starts_with("forward 5\r\n"));
+    }
+
+    #[test]
+    fn read_gzipped_input() {
+        let path = Path::new("data/fixtures/two.sample.gz");
+
+        assert_eq!(
+            read_lines(path).expect("valid gzip"),
+            [
+                "forward 5",
+                "down 5",
+                "forward 8",
+                "up 3",
+                "down 8",
+                "forward 2"
+            ]
+        );
+        assert_eq!(
+            read_all_text(Path::new("data/fixtures/two.input.gz"))
+                .expect("valid gzip"),
+            read_all_text(Path::new("data/two.input")).expect("plain text")
+        );
+    }
+
+    #[test]
+    fn read_multiple_inputs() {
+        let paths = [
+            PathBuf::from("data/fixtures/two.sample.gz"),
+            PathBuf::from("data/fixtures/two.sample.crlf"),
+        ];
+        let lines = read_inputs_lines_iter(&paths)
+            .expect("readable files")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .expect("readable lines");
+
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[..6], lines[6..]);
+        assert!(read_inputs_lines_iter(&[
+            paths[0].clone(),
+            PathBuf::from("data/missing.input")
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn non_empty_text_rejects_blank_input() {
+        let input = Input {
+            sample: true,
+            ..Input::default()
+        };
+
+        assert_eq!(
+            input.non_empty_text(" \n").unwrap_err().to_string(),
+            "sample input is empty"
+        );
+        assert_eq!(input.non_empty_text("1\n").expect("not empty"), "1\n");
+
+        let input = Input {
+            inline: Some("\n".to_owned()),
+            ..Input::default()
+        };
+        assert_eq!(
+            input.non_empty_text("1\n").unwrap_err().to_string(),
+            "inline input is empty"
+        );
+        assert!(input.non_empty_reader("1\n").is_err());
+
+        let input = Input {
+            inline: Some("\n \n12\n34\n".to_owned()),
+            ..Input::default()
+        };
+        let mut text = String::new();
+        input
+            .non_empty_reader("")
+            .expect("not empty")
+            .read_to_string(&mut text)
+            .expect("readable text");
+        assert_eq!(text, "12\n34\n");
+    }
+}
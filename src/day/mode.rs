@@ -0,0 +1,65 @@
+/// Declares a `Mode` enum parsed from kebab-case names along with the
+/// `ParseModeError` reported for any other name.
+///
+/// ```ignore
+/// mode_enum! {
+///     pub enum Mode {
+///         Paths => "paths",
+///         SmallCaveVisitTwiceOnce => "small-cave-visit-twice-once",
+///     }
+/// }
+/// ```
+macro_rules! mode_enum {
+    (
+        $visibility:vis enum $mode:ident {
+            $($variant:ident => $name:literal),+ $(,)?
+        }
+    ) => {
+        #[derive(Debug, thiserror::Error)]
+        #[error("Failed to parse mode from '{0}'")]
+        $visibility struct ParseModeError(String);
+
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        $visibility enum $mode {
+            $($variant),+
+        }
+
+        impl std::str::FromStr for $mode {
+            type Err = ParseModeError;
+
+            fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                match s {
+                    $($name => Ok($mode::$variant),)+
+                    _ => Err(ParseModeError(s.to_owned())),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use mode_enum;
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    mode_enum! {
+        enum Mode {
+            First => "first",
+            SecondMode => "second-mode",
+        }
+    }
+
+    #[test]
+    fn mode_from_str() {
+        assert_eq!(Mode::from_str("first").expect("valid"), Mode::First);
+        assert_eq!(
+            Mode::from_str("second-mode").expect("valid"),
+            Mode::SecondMode
+        );
+        assert_eq!(
+            Mode::from_str("SecondMode").unwrap_err().to_string(),
+            "Failed to parse mode from 'SecondMode'"
+        );
+    }
+}
@@ -1,86 +1,120 @@
 use std::{
-    cmp::Reverse, collections::HashMap, path::PathBuf, result, str::FromStr,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    result,
 };
 
+use rayon::prelude::*;
+use serde::Serialize;
 use structopt::{self, StructOpt};
 use thiserror;
 
-use super::read_lines;
+use super::{grid::Grid, mode::mode_enum, Format, Input};
 
-#[derive(Debug, thiserror::Error)]
-#[error("Failed to parse mode from '{0}'")]
-struct ParseModeError(String);
-
-#[derive(Debug, StructOpt)]
-enum Mode {
-    RiskLevel,
-    Basins,
-}
-
-impl FromStr for Mode {
-    type Err = ParseModeError;
-    fn from_str(mode: &str) -> result::Result<Self, Self::Err> {
-        match mode {
-            "risk-level" => Ok(Mode::RiskLevel),
-            "basins" => Ok(Mode::Basins),
-            _ => Err(ParseModeError(mode.to_owned())),
-        }
+mode_enum! {
+    enum Mode {
+        RiskLevel => "risk-level",
+        Basins => "basins",
     }
 }
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("risk-level"), long)]
     mode: Mode,
+
+    #[structopt(long)]
+    parallel: bool,
+
+    #[structopt(long)]
+    risk_grid: bool,
 }
 
 impl Command {
-    pub fn run(&self) -> anyhow::Result<()> {
-        let lines = read_lines(&self.input)?;
-        let map = HeightMap::parse(lines.iter().map(String::as_ref))?;
-
-        match self.mode {
-            Mode::Basins => report_basins(&map),
-            Mode::RiskLevel => report_risk_levels(&map),
+    pub fn run(&self, format: Format) -> anyhow::Result<()> {
+        let map = HeightMap::parse(self.input.non_empty_text(SAMPLE)?.lines())?;
+
+        match (self.mode, format) {
+            (Mode::Basins, Format::Text) => println!(
+                "Measure of three largest basins is: {}",
+                largest_basins_measure(&map, self.parallel)
+            ),
+            (Mode::Basins, Format::Json) => println!(
+                "{}",
+                serde_json::to_string(&LargestBasins {
+                    largest_basins_measure: largest_basins_measure(
+                        &map,
+                        self.parallel
+                    ),
+                })?
+            ),
+            (Mode::RiskLevel, Format::Text) => {
+                println!("The rish level is: {}", risk_level(&map))
+            }
+            (Mode::RiskLevel, Format::Json) => println!(
+                "{}",
+                serde_json::to_string(&RiskLevel {
+                    risk_level: risk_level(&map),
+                })?
+            ),
+        }
+        if self.risk_grid && format == Format::Text {
+            print!("{}", map.render_risk());
         }
 
         Ok(())
     }
 }
 
-fn report_basins(map: &HeightMap) {
-    let mut basins = map.basins();
+/// The `--format json` answer in risk level mode.
+#[derive(Debug, Serialize)]
+struct RiskLevel {
+    risk_level: u32,
+}
+
+/// The `--format json` answer in basins mode.
+#[derive(Debug, Serialize)]
+struct LargestBasins {
+    largest_basins_measure: usize,
+}
+
+fn largest_basins_measure(map: &HeightMap, parallel: bool) -> usize {
+    let mut basins = if parallel {
+        map.basins_parallel()
+    } else {
+        map.basins()
+    };
     basins.sort_by_key(|basin| Reverse(basin.size()));
-    let measure = basins
+    basins
         .iter()
         .take(3)
-        .fold(1, |measure, basin| measure * basin.size());
-    println!("Measure of three largest basins is: {}", measure);
+        .fold(1, |measure, basin| measure * basin.size())
 }
 
-fn report_risk_levels(map: &HeightMap) {
-    let risk_level: u32 = map
-        .risk_levels()
+fn risk_level(map: &HeightMap) -> u32 {
+    map.risk_levels()
         .iter()
-        .fold(0, |sum, risk_level| sum + *risk_level as u32);
-    println!("The rish level is: {}", risk_level);
+        .fold(0, |sum, risk_level| sum + *risk_level as u32)
 }
 
 #[derive(thiserror::Error, Debug)]
-enum Error {
+pub enum Error {
     #[error("failed to parse heightmap")]
     ParseHeightMap(),
+    #[error("heightmap has no rows")]
+    EmptyHeightMap(),
 }
 
 type Result<T> = result::Result<T, Error>;
 
-struct HeightMap(Vec<Vec<u8>>);
+/// The height of each location on the cave floor.
+pub struct HeightMap(Grid<u8>);
 
 impl HeightMap {
-    fn parse<'a, Iter>(lines: Iter) -> Result<HeightMap>
+    pub fn parse<'a, Iter>(lines: Iter) -> Result<HeightMap>
     where
         Iter: Iterator<Item = &'a str>,
     {
@@ -113,98 +147,71 @@ impl HeightMap {
             );
         }
 
-        Ok(HeightMap(map))
+        if map.is_empty() {
+            return Err(Error::EmptyHeightMap());
+        }
+        Ok(HeightMap(Grid::new(map)))
     }
 
     fn width(&self) -> usize {
-        self.0[0].len()
+        self.0.width()
     }
 
     fn length(&self) -> usize {
-        self.0.len()
+        self.0.height()
     }
 
-    fn low_points(&self) -> Vec<u8> {
-        let mut low_points = vec![];
-        let max_row = self.length() - 1;
-        let max_col = self.width() - 1;
-        let left = |row: usize, column: usize| self.0[row][column - 1];
-        let above = |row: usize, column: usize| self.0[row - 1][column];
-        let right = |row: usize, column: usize| self.0[row][column + 1];
-        let below = |row: usize, column: usize| self.0[row + 1][column];
-
-        for row in 0..=max_row {
-            for col in 0..=max_col {
-                let cell = self.0[row][col];
-                let low_point = match (row, col) {
-                    // top left
-                    (0, 0) => cell < right(0, 0) && cell < below(0, 0),
-                    // top right
-                    (0, col) if col == max_col => {
-                        cell < left(0, col) && cell < below(0, col)
-                    }
-                    // top
-                    (0, col) => {
-                        cell < left(0, col)
-                            && cell < right(0, col)
-                            && cell < below(0, col)
-                    }
-                    // bottom left
-                    (row, 0) if row == max_row => {
-                        cell < above(row, 0) && cell < right(row, 0)
-                    }
-                    // bottom right
-                    (row, col) if row == max_row && col == max_col => {
-                        cell < left(row, col) && cell < above(row, col)
-                    }
-                    // bottom
-                    (row, col) if row == max_row => {
-                        cell < left(row, col)
-                            && cell < above(row, col)
-                            && cell < right(row, col)
-                    }
-                    // left
-                    (row, 0) => {
-                        cell < above(row, 0)
-                            && cell < right(row, 0)
-                            && cell < below(row, 0)
-                    }
-                    // right
-                    (row, col) if col == max_col => {
-                        cell < left(row, col)
-                            && cell < above(row, col)
-                            && cell < below(row, col)
-                    }
-                    (row, col) => {
-                        cell < left(row, col)
-                            && cell < above(row, col)
-                            && cell < right(row, col)
-                            && cell < below(row, col)
-                    }
-                };
-                if low_point {
-                    low_points.push(cell);
-                }
-            }
-        }
+    pub fn low_points(&self) -> Vec<u8> {
+        self.low_point_positions()
+            .into_iter()
+            .map(|position| self.0[position])
+            .collect()
+    }
 
-        low_points
+    fn low_point_positions(&self) -> Vec<(usize, usize)> {
+        self.0
+            .positions()
+            .filter(|(row, col)| {
+                let cell = self.0[(*row, *col)];
+                self.0
+                    .neighbors4(*row, *col)
+                    .all(|neighbor| cell < self.0[neighbor])
+            })
+            .collect()
     }
 
-    fn risk_levels(&self) -> Vec<u8> {
+    pub fn risk_levels(&self) -> Vec<u8> {
         self.low_points()
             .into_iter()
             .map(|low_point| low_point + 1)
             .collect()
     }
 
-    fn basins(&self) -> Vec<Basin> {
+    /// Renders the map with each low point replaced by its risk level and
+    /// every other location as a dot.
+    fn render_risk(&self) -> String {
+        let mut grid = vec![vec!['.'; self.width()]; self.length()];
+        for (row, col) in self.low_point_positions() {
+            grid[row][col] = char::from(b'0' + self.0[(row, col)] + 1);
+        }
+        grid.into_iter()
+            .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+            .collect()
+    }
+
+    pub fn basins(&self) -> Vec<Basin> {
+        self.basin_mappings().basins()
+    }
+
+    /// Maps every cell to its basin in a single pass, merging the basins
+    /// that meet along the way.
+    pub fn basin_mappings(&self) -> BasinMappings {
         let mut mappings = BasinMappings::new(self.width(), self.length());
         let max_row = self.length() - 1;
         let max_col = self.width() - 1;
         for row in 0..=max_row {
             for col in 0..=max_col {
-                let cell = self.0[row][col];
+                let cell = self.0[(row, col)];
                 if cell == 9 {
                     mappings.set_basin_border(row, col);
                     continue;
@@ -246,27 +253,76 @@ impl HeightMap {
             }
         }
 
-        mappings.basins()
+        mappings
+    }
+
+    /// Finds the basins by flooding out from their lowest cells in
+    /// parallel. Seeding from every cell no higher than its neighbors, rather
+    /// than just the strict low points, gives a basin without one, such as a
+    /// plateau, a seed too so the basins match `basins`.
+    fn basins_parallel(&self) -> Vec<Basin> {
+        let seeds = self
+            .0
+            .positions()
+            .filter(|(row, col)| {
+                let cell = self.0[(*row, *col)];
+                cell != 9
+                    && self
+                        .0
+                        .neighbors4(*row, *col)
+                        .all(|neighbor| cell <= self.0[neighbor])
+            })
+            .collect::<Vec<_>>();
+        let seed_set = seeds.iter().copied().collect();
+        seeds
+            .par_iter()
+            .filter_map(|seed| self.flood_basin(*seed, &seed_set))
+            .map(|cells| Basin::flooded(&cells))
+            .collect()
+    }
+
+    /// Floods the cells of the basin containing `seed`. A basin holding
+    /// more than one seed is only reported from its first seed so the basins
+    /// don't overlap.
+    fn flood_basin(
+        &self,
+        seed: (usize, usize),
+        seeds: &HashSet<(usize, usize)>,
+    ) -> Option<HashSet<(usize, usize)>> {
+        let mut visited = HashSet::from([seed]);
+        let mut pending = vec![seed];
+        while let Some((row, col)) = pending.pop() {
+            if (row, col) < seed && seeds.contains(&(row, col)) {
+                return None;
+            }
+            for neighbor in self.0.neighbors4(row, col) {
+                if self.0[neighbor] != 9 && visited.insert(neighbor) {
+                    pending.push(neighbor);
+                }
+            }
+        }
+        Some(visited)
     }
 }
 
-struct BasinMappings {
-    names: Vec<String>,
+/// Every cell's basin, as ids joined in a union-find forest when basins
+/// meet.
+pub struct BasinMappings {
+    parents: Vec<usize>,
     mappings: Vec<Vec<(Option<u8>, Option<usize>)>>,
 }
 
 impl BasinMappings {
     fn new(width: usize, length: usize) -> Self {
         BasinMappings {
-            names: vec![],
+            parents: vec![],
             mappings: vec![vec![(None, None); width]; length],
         }
     }
 
     fn new_basin(&mut self, row: usize, col: usize, value: u8) {
-        let basin = self.names.len();
-        let name = (basin + 1).to_string();
-        self.names.push(name);
+        let basin = self.parents.len();
+        self.parents.push(basin);
         self.set_basin(row, col, value, basin);
     }
 
@@ -275,20 +331,16 @@ impl BasinMappings {
     }
 
     fn merge_basin(&mut self, basin: usize, other_basin: usize) {
-        let basin_name = self.names[basin].clone();
-        let other_basin_name = self.names[other_basin].clone();
-        let mut names = self
-            .names
-            .iter()
-            .map(|name| {
-                if *name == other_basin_name {
-                    basin_name.clone()
-                } else {
-                    name.clone()
-                }
-            })
-            .collect::<Vec<String>>();
-        std::mem::swap(&mut self.names, &mut names)
+        let root = self.find(basin);
+        let other_root = self.find(other_basin);
+        self.parents[other_root] = root;
+    }
+
+    fn find(&self, mut basin: usize) -> usize {
+        while self.parents[basin] != basin {
+            basin = self.parents[basin];
+        }
+        basin
     }
 
     fn set_basin_border(&mut self, row: usize, col: usize) {
@@ -303,8 +355,15 @@ impl BasinMappings {
         self.mappings[row - 1][col].1
     }
 
+    /// Returns the same id for every cell of a basin, after merges, and
+    /// `None` for the borders.
+    pub fn basin_id_at(&self, row: usize, col: usize) -> Option<usize> {
+        let (_, basin) = self.mappings.get(row)?.get(col)?;
+        Some(self.find((*basin)?))
+    }
+
     fn basins(&self) -> Vec<Basin> {
-        let mut basins: HashMap<&str, Vec<BasinPoint>> = HashMap::new();
+        let mut basins: HashMap<usize, Vec<BasinPoint>> = HashMap::new();
         for row in self.mappings.iter() {
             for cell in row.iter() {
                 if let (Some(height), Some(basin)) = cell {
@@ -312,48 +371,60 @@ impl BasinMappings {
                         continue;
                     }
 
-                    let basin = &*self.names[*basin];
-                    basins
-                        .entry(basin)
-                        .or_insert_with(Vec::new)
-                        .push(BasinPoint {})
+                    let basin = self.find(*basin);
+                    basins.entry(basin).or_default().push(BasinPoint {})
                 }
             }
         }
 
         basins
-            .into_iter()
-            .map(|(_name, points)| Basin { points })
+            .into_values()
+            .map(|points| Basin { points })
             .collect()
     }
 }
 
-struct Basin {
+pub struct Basin {
     points: Vec<BasinPoint>,
 }
 
 impl Basin {
-    fn size(&self) -> usize {
+    fn flooded(cells: &HashSet<(usize, usize)>) -> Self {
+        Basin {
+            points: cells.iter().map(|_| BasinPoint {}).collect(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
         self.points.len()
     }
 }
 
 struct BasinPoint;
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
 #[cfg(test)]
 mod tests {
-    use super::HeightMap;
+    use std::cmp::Reverse;
+
+    use super::{Basin, HeightMap, SAMPLE};
 
     #[test]
     fn height_map_parse() {
-        let map = HeightMap::parse(INPUT.split('\n')).expect("valid input");
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
         assert_eq!(map.width(), 10);
         assert_eq!(map.length(), 5);
     }
 
     #[test]
     fn height_map_low_points() {
-        let map = HeightMap::parse(INPUT.split('\n')).expect("valid input");
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
 
         let low_points = map.low_points();
 
@@ -362,20 +433,34 @@ mod tests {
 
     #[test]
     fn height_map_risk_levels() {
-        let map = HeightMap::parse(INPUT.split('\n')).expect("valid input");
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
 
         let risk_levels = map.risk_levels();
 
         assert_eq!(risk_levels, vec![2, 1, 6, 6]);
     }
 
+    #[test]
+    fn height_map_render_risk() {
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
+
+        assert_eq!(
+            map.render_risk(),
+            ".2.......1\n\
+             ..........\n\
+             ..6.......\n\
+             ..........\n\
+             ......6...\n"
+        );
+    }
+
     #[test]
     fn height_map_basins() {
-        let map = HeightMap::parse(INPUT.split('\n')).expect("valid input");
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
 
         let mut basins = map.basins();
 
-        basins.sort_by(|a, b| b.points.len().cmp(&a.points.len()));
+        basins.sort_by_key(|basin| Reverse(basin.points.len()));
         let measure = basins
             .iter()
             .take(3usize)
@@ -384,9 +469,102 @@ mod tests {
         assert_eq!(measure, 1134);
     }
 
-    const INPUT: &str = r"2199943210
+    #[test]
+    fn basin_mappings_basin_id_at() {
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
+
+        let mappings = map.basin_mappings();
+
+        // the top left basin
+        assert!(mappings.basin_id_at(0, 0).is_some());
+        assert_eq!(mappings.basin_id_at(0, 0), mappings.basin_id_at(1, 0));
+        // the top right basin
+        assert_eq!(mappings.basin_id_at(0, 9), mappings.basin_id_at(2, 9));
+        assert_eq!(mappings.basin_id_at(0, 5), mappings.basin_id_at(1, 6));
+        assert_ne!(mappings.basin_id_at(0, 0), mappings.basin_id_at(0, 9));
+        // the middle and bottom right basins
+        assert_ne!(mappings.basin_id_at(2, 2), mappings.basin_id_at(4, 6));
+        assert_eq!(mappings.basin_id_at(0, 2), None);
+        assert_eq!(mappings.basin_id_at(5, 0), None);
+    }
+
+    #[test]
+    fn height_map_basins_parallel() {
+        let map = HeightMap::parse(SAMPLE.split('\n')).expect("valid input");
+
+        let mut sizes = map
+            .basins()
+            .iter()
+            .map(|basin| basin.size())
+            .collect::<Vec<_>>();
+        let mut parallel_sizes = map
+            .basins_parallel()
+            .iter()
+            .map(|basin| basin.size())
+            .collect::<Vec<_>>();
+        sizes.sort_unstable();
+        parallel_sizes.sort_unstable();
+
+        assert_eq!(sizes, vec![3, 9, 9, 14]);
+        assert_eq!(parallel_sizes, sizes);
+    }
+
+    #[test]
+    fn height_map_basins_merging_across_rows() {
+        let map =
+            HeightMap::parse(MERGING_BASINS.split('\n')).expect("valid input");
+
+        let sorted_sizes = |basins: Vec<Basin>| {
+            let mut sizes =
+                basins.iter().map(|basin| basin.size()).collect::<Vec<_>>();
+            sizes.sort_unstable();
+            sizes
+        };
+
+        assert_eq!(map.low_point_positions(), vec![(2, 3), (4, 3)]);
+        assert_eq!(sorted_sizes(map.basins()), vec![7, 15]);
+        assert_eq!(sorted_sizes(map.basins_parallel()), vec![7, 15]);
+    }
+
+    #[test]
+    fn height_map_basins_parallel_with_plateaus() {
+        let map =
+            HeightMap::parse(PLATEAU_BASINS.split('\n')).expect("valid input");
+
+        let sorted_sizes = |basins: Vec<Basin>| {
+            let mut sizes =
+                basins.iter().map(|basin| basin.size()).collect::<Vec<_>>();
+            sizes.sort_unstable();
+            sizes
+        };
+
+        assert_eq!(sorted_sizes(map.basins()), vec![2, 3, 3, 9, 9, 14]);
+        assert_eq!(
+            sorted_sizes(map.basins_parallel()),
+            vec![2, 3, 3, 9, 9, 14]
+        );
+    }
+
+    // the sample with two plateau basins that have no strict low point
+    // below it
+    const PLATEAU_BASINS: &str = r"2199943210
 3987894921
 9856789892
 8767896789
-9899965678";
+9899965678
+9999999999
+1199955599";
+
+    // four columns that only meet in the third row, where the row scan
+    // merges each of them into the basin on its left
+    const MERGING_BASINS: &str = r"4949494
+3939393
+3210123
+9999999
+8765678";
+
+    #[test]
+    fn height_map_parse_empty() {
+        assert!(HeightMap::parse("".lines()).is_err());
+    }
 }
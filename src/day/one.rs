@@ -1,40 +1,152 @@
-use anyhow::{anyhow, Result};
+use std::{cmp::Ordering, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
 use colored::*;
-use structopt::{self, StructOpt};
+use structopt::{self, clap::AppSettings, StructOpt};
+
+use super::read_lines;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to parse report format from '{0}'")]
+pub struct ParseReportFormatError(String);
+
+/// How the measures or window sums are listed: prose or CSV rows of the
+/// change from the prior value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Csv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ParseReportFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(ParseReportFormatError(s.to_owned())),
+        }
+    }
+}
 
 #[derive(Debug, StructOpt)]
+#[structopt(setting(AppSettings::AllowNegativeNumbers))]
 pub struct Command {
-    #[structopt(required(true))]
-    depth_measurements: Vec<usize>,
+    #[structopt(required_unless_one(&["sample", "input"]))]
+    depth_measurements: Vec<i64>,
+
+    /// Use the puzzle's example measurements instead of arguments
+    #[structopt(long, conflicts_with_all(&["depth-measurements", "input"]))]
+    sample: bool,
+
+    /// Read the measurements from a file, one per line, instead of arguments
+    #[structopt(
+        long,
+        parse(from_os_str),
+        conflicts_with("depth-measurements")
+    )]
+    input: Option<PathBuf>,
 
     #[structopt(long, default_value("1"))]
     window_size: usize,
+
+    /// Count the increases for windows of 1 and 3 together
+    #[structopt(long)]
+    both: bool,
+
+    /// List the measures as text or csv
+    #[structopt(long, default_value("text"))]
+    format: ReportFormat,
 }
 
 impl Command {
     pub fn run(&self) -> Result<()> {
-        match self.window_size {
-            0 => Err(anyhow!("window-size must be 1 or greater")),
-            1 => {
+        if self.sample {
+            return Command {
+                depth_measurements: SAMPLE.to_vec(),
+                sample: false,
+                input: None,
+                ..*self
+            }
+            .run();
+        }
+        if let Some(input) = &self.input {
+            return Command {
+                depth_measurements: parse_measurements(read_lines(input)?)?,
+                sample: false,
+                input: None,
+                ..*self
+            }
+            .run();
+        }
+        if self.both {
+            let (increased_measures, increased_sums) = self.both_increases();
+            println!(
+                "window 1: {} increases / window 3: {} increases",
+                increased_measures, increased_sums
+            );
+            return Ok(());
+        }
+
+        match (self.window_size, self.format) {
+            (0, _) => Err(anyhow!("window-size must be 1 or greater")),
+            (_, ReportFormat::Csv) => {
+                self.report_csv();
+                Ok(())
+            }
+            (1, _) => {
                 self.report_measures();
+                println!("{} increasing measures", self.analyze().increases);
                 Ok(())
             }
             _ => {
                 self.report_sums();
+                println!("{} increasing sums", self.analyze().increases);
                 Ok(())
             }
         }
     }
 
+    /// Counts the increasing measures and the increasing sums of three
+    /// measure windows.
+    fn both_increases(&self) -> (usize, usize) {
+        (
+            count_increases(&self.depth_measurements, 1),
+            count_increases(&self.depth_measurements, 3),
+        )
+    }
+
+    fn analyze(&self) -> DepthReport {
+        DepthReport {
+            increases: count_increases(
+                &self.depth_measurements,
+                self.window_size,
+            ),
+            total: self.depth_measurements.len(),
+        }
+    }
+
+    /// Prints a CSV row for each window's sum, a window of one being just
+    /// the measure.
+    fn report_csv(&self) {
+        println!("index,value,delta,direction");
+        let sums = window_sums(&self.depth_measurements, self.window_size);
+        let priors =
+            std::iter::once(None).chain(sums.iter().copied().map(Some));
+        for (index, (sum, prior)) in sums.iter().zip(priors).enumerate() {
+            println!("{}", csv_row(index, *sum, prior));
+        }
+    }
+
+    /// Prints how each measure compares to the prior one.
     fn report_measures(&self) {
-        let mut increased_measures = 0;
         let mut prior = None;
         for measure in &self.depth_measurements {
             match prior {
                 None => println!("{} (N/A - no previous measurement)", measure),
                 Some(prior) if prior < measure => {
                     println!("{} ({})", measure, "increased".bold());
-                    increased_measures += 1;
                 }
                 Some(prior) if prior > measure => {
                     println!("{} (decreased)", measure)
@@ -44,16 +156,17 @@ impl Command {
 
             prior = Some(measure);
         }
-
-        println!("{} increasing measures", increased_measures);
     }
 
+    /// Prints how each window's sum compares to the prior one.
     fn report_sums(&self) {
-        let mut increased_sums = 0;
         let mut prior = None;
-        let measure_count = self.depth_measurements.len();
-        for index in 0..=(measure_count - self.window_size) {
-            let sum: usize = self.depth_measurements
+        // there are no windows at all when the window is wider than the
+        // measurements
+        let window_count = (self.depth_measurements.len() + 1)
+            .saturating_sub(self.window_size);
+        for index in 0..window_count {
+            let sum: i64 = self.depth_measurements
                 [index..(self.window_size + index)]
                 .iter()
                 .sum();
@@ -61,7 +174,6 @@ impl Command {
                 None => println!("{} (N/A - no previous sum)", sum),
                 Some(prior) if prior < sum => {
                     println!("{} ({})", sum, "increased".bold());
-                    increased_sums += 1;
                 }
                 Some(prior) if prior > sum => println!("{} (decreased)", sum),
                 _ => println!("{} (no change)", sum),
@@ -69,7 +181,173 @@ impl Command {
 
             prior = Some(sum);
         }
+    }
+}
+
+/// The number of increases found across the `total` measurements.
+#[derive(Debug, PartialEq, Eq)]
+struct DepthReport {
+    increases: usize,
+    total: usize,
+}
+
+/// The number of windows of `window` measurements whose sum is larger than
+/// the window before.
+fn count_increases(measurements: &[i64], window: usize) -> usize {
+    window_sums(measurements, window)
+        .windows(2)
+        .filter(|pair| pair[0] < pair[1])
+        .count()
+}
+
+fn window_sums(measurements: &[i64], window: usize) -> Vec<i64> {
+    measurements
+        .windows(window)
+        .map(|window| window.iter().sum())
+        .collect()
+}
+
+/// A `index,value,delta,direction` row, where the first value has no delta
+/// and an `na` direction.
+fn csv_row(index: usize, value: i64, prior: Option<i64>) -> String {
+    match prior {
+        None => format!("{},{},,na", index, value),
+        Some(prior) => {
+            let delta = value - prior;
+            let direction = match delta.cmp(&0) {
+                Ordering::Greater => "up",
+                Ordering::Less => "down",
+                Ordering::Equal => "flat",
+            };
+            format!("{},{},{},{}", index, value, delta, direction)
+        }
+    }
+}
+
+/// Parses the measurements, usually one per line though any whitespace
+/// separates them.
+fn parse_measurements(lines: Vec<String>) -> Result<Vec<i64>> {
+    lines
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .map(|entry| {
+            entry.parse().with_context(|| {
+                format!("failed to parse depth measurement '{}'", entry)
+            })
+        })
+        .collect()
+}
+
+/// The example measurements from the puzzle description.
+const SAMPLE: [i64; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        count_increases, csv_row, parse_measurements, Command, DepthReport,
+        ReportFormat, SAMPLE,
+    };
+
+    #[test]
+    fn both_increases() {
+        let command = Command {
+            depth_measurements: SAMPLE.to_vec(),
+            window_size: 1,
+            both: true,
+            sample: false,
+            input: None,
+            format: ReportFormat::Text,
+        };
+
+        assert_eq!(command.both_increases(), (7, 5));
+    }
+
+    #[test]
+    fn count_increases_test() {
+        assert_eq!(count_increases(&SAMPLE, 1), 7);
+        assert_eq!(count_increases(&SAMPLE, 3), 5);
+        assert_eq!(count_increases(&SAMPLE[..2], 3), 0);
+    }
+
+    #[test]
+    fn signed_measurements() {
+        let measurements = [-3, -5, 0, 2, -1, 4];
+
+        assert_eq!(count_increases(&measurements, 1), 3);
+        assert_eq!(count_increases(&measurements, 3), 3);
+        assert_eq!(
+            parse_measurements(vec!["-3 -5".to_owned(), "0".to_owned()])
+                .expect("valid measurements"),
+            [-3, -5, 0]
+        );
+    }
+
+    #[test]
+    fn csv_row_test() {
+        assert_eq!(csv_row(0, 199, None), "0,199,,na");
+        assert_eq!(csv_row(1, 200, Some(199)), "1,200,1,up");
+        assert_eq!(csv_row(4, 200, Some(210)), "4,200,-10,down");
+        assert_eq!(csv_row(5, 607, Some(607)), "5,607,0,flat");
+    }
+
+    #[test]
+    fn report_format_from_str() {
+        assert_eq!(
+            "csv".parse::<ReportFormat>().expect("valid"),
+            ReportFormat::Csv
+        );
+        assert!("json".parse::<ReportFormat>().is_err());
+    }
+
+    #[test]
+    fn analyze() {
+        let command = Command {
+            depth_measurements: SAMPLE.to_vec(),
+            window_size: 3,
+            both: false,
+            sample: false,
+            input: None,
+            format: ReportFormat::Text,
+        };
+
+        assert_eq!(
+            command.analyze(),
+            DepthReport {
+                increases: 5,
+                total: 10
+            }
+        );
+    }
+
+    #[test]
+    fn window_size_exceeds_measurement_count() {
+        let command = Command {
+            depth_measurements: vec![199],
+            window_size: 3,
+            both: false,
+            sample: false,
+            input: None,
+            format: ReportFormat::Text,
+        };
+
+        assert!(command.run().is_ok());
+        assert_eq!(
+            command.analyze(),
+            DepthReport {
+                increases: 0,
+                total: 1
+            }
+        );
+    }
+
+    #[test]
+    fn parse_measurements_test() {
+        let lines = ["199", " 200", "", "208 210"].map(str::to_owned).to_vec();
 
-        println!("{} increasing sums", increased_sums);
+        assert_eq!(
+            parse_measurements(lines).expect("valid measurements"),
+            [199, 200, 208, 210]
+        );
+        assert!(parse_measurements(vec!["deep".to_owned()]).is_err());
     }
 }
@@ -1,122 +1,221 @@
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{Day, Format, Inputs};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    inputs: Inputs,
 }
 
 impl Command {
-    pub fn run(&self) -> Result<()> {
-        let submarines = CrabSubmarineManager::parse(
-            read_lines(&self.input)?.iter().map(String::as_ref),
-        )?;
+    pub fn run(&self, format: Format) -> Result<()> {
+        let submarines =
+            CrabSubmarineManager::parse(self.inputs.lines_iter(SAMPLE)?)?;
+        let (linear_position, linear_fuel) =
+            submarines.optimum(FuelModel::Linear);
+        if format == Format::Json {
+            let optimum = Optimum {
+                optimal_position: linear_position,
+                fuel: linear_fuel,
+            };
+            println!("{}", serde_json::to_string(&optimum)?);
+            return Ok(());
+        }
+        let (median, minimum, maximum) = match (
+            submarines.median(),
+            submarines.minimum(),
+            submarines.maximum(),
+        ) {
+            (Some(median), Some(minimum), Some(maximum)) => {
+                (median, minimum, maximum)
+            }
+            _ => return Err(anyhow!("there are no crab submarines")),
+        };
 
         let (sum, count) = submarines
             .positions()
             .iter()
             .fold((0, 0), |(sum, count), position| (sum + position, count + 1));
         let average = sum as f64 / count as f64;
-        let mut sorted_positions: Vec<u32> =
-            submarines.positions().to_vec();
-        sorted_positions.sort_unstable();
-        let median = sorted_positions[sorted_positions.len() / 2];
 
+        let (optimum, optimum_cost) = submarines.triangular_optimum();
         println!(
-            "The average position is {}; the cost to move to rounded average ({}) is {}",
+            "The average position is {}; the cost to move to the cheaper neighbor of the average ({}) is {}",
             average,
-            average.round() as u32,
-            submarines.cost_to_move(average.round() as u32)
+            optimum,
+            optimum_cost
         );
         println!(
             "The median position is {}; the cost to move to median is {}",
             median,
             submarines.cost_to_move(median)
         );
-        let minimum = sorted_positions[0];
         println!(
             "The minimum position is {}; the cost to move to minimum is {}",
             minimum,
             submarines.cost_to_move(minimum)
         );
-        let maximum = sorted_positions[sorted_positions.len() - 1];
         println!(
             "The maximum position is {}; the cost to move to maximum is {}",
             maximum,
             submarines.cost_to_move(maximum)
         );
-        let mut move_costs = vec![0; (maximum - minimum + 1) as usize];
-        for (index, position) in (minimum..=maximum).enumerate() {
-            move_costs[index] = submarines.cost_to_move(position);
-        }
-        let (lowest_cost_index, lowest_cost) =
-            move_costs.iter().copied().enumerate().fold(
-                (0, u32::MAX),
-                |(lowest_cost_index, lowest_cost), (index, cost)| {
-                    if cost < lowest_cost {
-                        (index, cost)
-                    } else {
-                        (lowest_cost_index, lowest_cost)
-                    }
-                },
-            );
+        let (constant_position, constant_fuel) =
+            submarines.optimum(FuelModel::Constant);
         println!(
             "Moving to position {} has the lowest cost of {}",
-            (minimum as usize) + lowest_cost_index,
-            lowest_cost
+            linear_position, linear_fuel
+        );
+        println!(
+            "Moving to position {} at a constant rate has the lowest cost of {}",
+            constant_position, constant_fuel
         );
         Ok(())
     }
 }
 
+impl Day for Command {
+    fn part_one(&self, input: &str) -> Result<String> {
+        let submarines = CrabSubmarineManager::parse(input.lines().map(Ok))?;
+        Ok(submarines.minimum_fuel(FuelModel::Constant).to_string())
+    }
+
+    fn part_two(&self, input: &str) -> Result<String> {
+        let submarines = CrabSubmarineManager::parse(input.lines().map(Ok))?;
+        Ok(submarines.minimum_fuel(FuelModel::Linear).to_string())
+    }
+}
+
+/// The `--format json` answer: the cheapest position to align on and the
+/// fuel it takes.
+#[derive(Debug, Serialize)]
+struct Optimum {
+    optimal_position: u32,
+    fuel: u32,
+}
+
+/// How much fuel a crab submarine burns for each step it moves. Constant
+/// burns one unit per step while linear burns one more unit than the prior
+/// step.
+#[derive(Clone, Copy, Debug)]
+enum FuelModel {
+    Constant,
+    Linear,
+}
+
 struct CrabSubmarineManager {
     positions: Vec<u32>,
+    /// Sorted once so the median, minimum and maximum don't each need their
+    /// own pass.
+    sorted_positions: Vec<u32>,
 }
 
 impl CrabSubmarineManager {
-    fn parse<'a, Iter>(input: Iter) -> Result<CrabSubmarineManager>
+    fn parse<Iter, Line>(input: Iter) -> Result<CrabSubmarineManager>
     where
-        Iter: Iterator<Item = &'a str>,
+        Iter: Iterator<Item = Result<Line>>,
+        Line: AsRef<str>,
     {
-        let positions = input
-            .flat_map(|line| line.split(','))
-            .map(str::trim)
-            .map(|entry| {
-                entry.parse::<u32>().with_context(|| {
+        let mut positions = vec![];
+        for line in input {
+            for entry in line?.as_ref().split(',').map(str::trim) {
+                positions.push(entry.parse::<u32>().with_context(|| {
                     format!("failed to parse position '{}'", entry)
-                })
-            })
-            .collect::<Result<Vec<u32>>>()?;
-        Ok(CrabSubmarineManager { positions })
+                })?);
+            }
+        }
+        let mut sorted_positions = positions.clone();
+        sorted_positions.sort_unstable();
+        Ok(CrabSubmarineManager {
+            positions,
+            sorted_positions,
+        })
     }
 
     fn positions(&self) -> &[u32] {
         &self.positions
     }
 
+    fn sorted_positions(&self) -> &[u32] {
+        &self.sorted_positions
+    }
+
+    fn median(&self) -> Option<u32> {
+        let sorted_positions = self.sorted_positions();
+        sorted_positions.get(sorted_positions.len() / 2).copied()
+    }
+
+    fn minimum(&self) -> Option<u32> {
+        self.sorted_positions().first().copied()
+    }
+
+    fn maximum(&self) -> Option<u32> {
+        self.sorted_positions().last().copied()
+    }
+
     fn cost_to_move(&self, position: u32) -> u32 {
+        self.fuel_to_move(position, FuelModel::Linear)
+    }
+
+    fn fuel_to_move(&self, position: u32, model: FuelModel) -> u32 {
         self.positions
             .iter()
             .copied()
             .fold(0, |cost, current_position| {
-                let step_count = if current_position > position {
-                    current_position - position
-                } else {
-                    position - current_position
-                };
-                cost + (1..=step_count).into_iter().sum::<u32>()
+                let step_count = current_position.abs_diff(position);
+                cost + match model {
+                    FuelModel::Constant => step_count,
+                    FuelModel::Linear => (1..=step_count).sum::<u32>(),
+                }
             })
     }
+
+    /// Returns the lowest position between the outermost submarines that
+    /// costs the least fuel to move every submarine to, and that fuel.
+    fn optimum(&self, model: FuelModel) -> (u32, u32) {
+        let minimum = self.minimum().unwrap_or(0);
+        let maximum = self.maximum().unwrap_or(0);
+        (minimum..=maximum)
+            .map(|position| (position, self.fuel_to_move(position, model)))
+            .min_by_key(|(_, fuel)| *fuel)
+            .expect("range is never empty")
+    }
+
+    fn minimum_fuel(&self, model: FuelModel) -> u32 {
+        self.optimum(model).1
+    }
+
+    /// The triangular cost optimum lies at the floor or the ceiling of the
+    /// mean so both are evaluated and the cheaper position and its cost are
+    /// returned.
+    fn triangular_optimum(&self) -> (u32, u32) {
+        let sum: u64 = self.positions.iter().map(|p| *p as u64).sum();
+        let mean = sum as f64 / self.positions.len() as f64;
+        let floor = mean.floor() as u32;
+        let ceil = mean.ceil() as u32;
+        let floor_cost = self.cost_to_move(floor);
+        let ceil_cost = self.cost_to_move(ceil);
+        if ceil_cost < floor_cost {
+            (ceil, ceil_cost)
+        } else {
+            (floor, floor_cost)
+        }
+    }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = "16,1,2,0,4,2,7,1,2,14\n";
+
 #[cfg(test)]
 mod tests {
-    use super::CrabSubmarineManager;
+    use structopt::StructOpt;
+
+    use super::{
+        Command, CrabSubmarineManager, Day, FuelModel, Optimum, SAMPLE,
+    };
 
     #[test]
     fn parse_test() {
@@ -127,8 +226,9 @@ mod tests {
             .map(u32::to_string)
             .collect::<Vec<_>>()
             .join(",");
-        let submarines = CrabSubmarineManager::parse([&*input].into_iter())
-            .expect("valid input");
+        let submarines =
+            CrabSubmarineManager::parse([&*input].into_iter().map(Ok))
+                .expect("valid input");
 
         assert_eq!(submarines.positions(), expected);
     }
@@ -148,9 +248,89 @@ mod tests {
         println!("{}", median);
     }
 
+    #[test]
+    fn sorted_positions() {
+        let positions: Vec<u32> = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        let submarines = CrabSubmarineManager::parse(
+            ["16,1,2,0,4,2,7,1,2,14"].into_iter().map(Ok),
+        )
+        .expect("valid input");
+        let mut sorted = positions.clone();
+        sorted.sort();
+
+        assert_eq!(submarines.positions(), positions);
+        assert_eq!(submarines.median(), Some(sorted[sorted.len() / 2]));
+        assert_eq!(submarines.minimum(), positions.iter().copied().min());
+        assert_eq!(submarines.maximum(), positions.iter().copied().max());
+        assert_eq!(
+            (
+                submarines.median(),
+                submarines.minimum(),
+                submarines.maximum()
+            ),
+            (Some(2), Some(0), Some(16))
+        );
+    }
+
+    #[test]
+    fn minimum_fuel() {
+        let submarines = CrabSubmarineManager::parse(
+            ["16,1,2,0,4,2,7,1,2,14"].into_iter().map(Ok),
+        )
+        .expect("valid input");
+
+        assert_eq!(submarines.optimum(FuelModel::Constant), (2, 37));
+        assert_eq!(submarines.minimum_fuel(FuelModel::Constant), 37);
+        assert_eq!(submarines.optimum(FuelModel::Linear), (5, 168));
+        assert_eq!(submarines.minimum_fuel(FuelModel::Linear), 168);
+    }
+
+    #[test]
+    fn optimum_json() {
+        let optimum = Optimum {
+            optimal_position: 5,
+            fuel: 168,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&optimum).expect("serializable"),
+            r#"{"optimal_position":5,"fuel":168}"#
+        );
+    }
+
+    #[test]
+    fn triangular_optimum() {
+        let submarines = CrabSubmarineManager::parse(
+            ["16,1,2,0,4,2,7,1,2,14"].into_iter().map(Ok),
+        )
+        .expect("valid input");
+
+        assert_eq!(submarines.triangular_optimum(), (5, 168));
+    }
+
+    #[test]
+    fn triangular_optimum_half_mean_picks_cheaper_neighbor() {
+        // the mean is 1.5 which rounds to the more expensive position 2
+        let submarines =
+            CrabSubmarineManager::parse(["0,0,0,6"].into_iter().map(Ok))
+                .expect("valid input");
+
+        assert_eq!(submarines.cost_to_move(2), 19);
+        assert_eq!(submarines.triangular_optimum(), (1, 18));
+    }
+
     #[test]
     fn sum_of_steps() {
-        let sum: u32 = (1..=11).into_iter().sum();
+        let sum: u32 = (1..=11).sum();
         assert_eq!(sum, 66);
     }
+
+    #[test]
+    fn day_parts() {
+        let command = Command::from_iter_safe(["seven", "--sample"])
+            .expect("valid arguments");
+
+        assert_eq!(command.part_one(SAMPLE).expect("valid input"), "37");
+        assert_eq!(command.part_two(SAMPLE).expect("valid input"), "168");
+    }
 }
@@ -1,28 +1,33 @@
-use std::path::PathBuf;
+use std::{ops::RangeInclusive, str::FromStr};
 
+use regex::Regex;
 use structopt::{self, StructOpt};
 
-use super::read_all_text;
+use super::{geometry::Point, Input};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 }
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
-        let input = read_all_text(&self.input)?;
-        println!("seventeen input: {input}");
+        let target = TargetArea::from_str(&self.input.text(SAMPLE)?)?;
+        if let Some(highest_y) = target.highest_y() {
+            println!("Highest y position reached: {}", highest_y);
+        } else {
+            println!("No launch velocity reaches the target area");
+        }
         Ok(())
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
-struct Position {
-    x: i32,
-    y: i32,
-}
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to parse target area from '{0}'")]
+pub struct ParseTargetAreaError(String);
+
+type Position = Point<i32>;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 struct Velocity {
@@ -41,7 +46,127 @@ impl Probe {
         let position = Position::default();
         Probe { position, velocity }
     }
+
+    /// Moves the probe by its velocity, then drag slows the x velocity
+    /// towards zero and gravity decreases the y velocity.
+    fn step(&mut self) {
+        self.position.x += self.velocity.x;
+        self.position.y += self.velocity.y;
+        self.velocity.x -= self.velocity.x.signum();
+        self.velocity.y -= 1;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TargetArea {
+    x: RangeInclusive<i32>,
+    y: RangeInclusive<i32>,
+}
+
+impl TargetArea {
+    fn contains(&self, position: Position) -> bool {
+        self.x.contains(&position.x) && self.y.contains(&position.y)
+    }
+
+    /// Launches the probe returning the highest y position reached when the
+    /// probe is within the target area after any step.
+    fn launch(&self, velocity: Velocity) -> Option<i32> {
+        let mut probe = Probe::launch(velocity);
+        let mut highest_y = probe.position.y;
+        // once the probe is below the target and falling it never returns
+        while probe.position.y >= *self.y.start() || probe.velocity.y > 0 {
+            probe.step();
+            highest_y = highest_y.max(probe.position.y);
+            if self.contains(probe.position) {
+                return Some(highest_y);
+            }
+        }
+        None
+    }
+
+    /// Tries every launch velocity that could reach a target area below and
+    /// to the right of the launch position, returning the highest y position
+    /// reached by any of them.
+    fn highest_y(&self) -> Option<i32> {
+        let max_y_velocity = self.y.start().abs().max(self.y.end().abs());
+        (0..=*self.x.end())
+            .flat_map(|x| {
+                (-max_y_velocity..=max_y_velocity)
+                    .map(move |y| Velocity { x, y })
+            })
+            .filter_map(|velocity| self.launch(velocity))
+            .max()
+    }
 }
 
+impl FromStr for TargetArea {
+    type Err = ParseTargetAreaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error = || ParseTargetAreaError(s.trim().to_owned());
+        let regex = Regex::new(
+            r"^target area: x=(-?\d+)\.\.(-?\d+), y=(-?\d+)\.\.(-?\d+)$",
+        )
+        .map_err(|_| parse_error())?;
+        let captures = regex.captures(s.trim()).ok_or_else(parse_error)?;
+        let bounds = (1..=4)
+            .map(|index| captures[index].parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| parse_error())?;
+        Ok(TargetArea {
+            x: bounds[0].min(bounds[1])..=bounds[0].max(bounds[1]),
+            y: bounds[2].min(bounds[3])..=bounds[2].max(bounds[3]),
+        })
+    }
+}
+
+/// The example input from the puzzle description.
+const SAMPLE: &str = "target area: x=20..30, y=-10..-5\n";
+
 #[cfg(test)]
-mod test {}
+mod test {
+    use std::str::FromStr;
+
+    use super::{Position, Probe, TargetArea, Velocity, SAMPLE};
+
+    #[test]
+    fn target_area_from_str() {
+        let target = TargetArea::from_str(SAMPLE).expect("valid input");
+
+        assert_eq!(
+            target,
+            TargetArea {
+                x: 20..=30,
+                y: -10..=-5
+            }
+        );
+        assert!(TargetArea::from_str("target area: x=20..30").is_err());
+    }
+
+    #[test]
+    fn probe_step() {
+        let mut probe = Probe::launch(Velocity { x: 7, y: 2 });
+
+        probe.step();
+        probe.step();
+
+        assert_eq!(probe.position, Position { x: 13, y: 3 });
+        assert_eq!(probe.velocity, Velocity { x: 5, y: 0 });
+    }
+
+    #[test]
+    fn target_area_launch() {
+        let target = TargetArea::from_str(SAMPLE).expect("valid input");
+
+        assert_eq!(target.launch(Velocity { x: 7, y: 2 }), Some(3));
+        assert_eq!(target.launch(Velocity { x: 6, y: 9 }), Some(45));
+        assert_eq!(target.launch(Velocity { x: 17, y: -4 }), None);
+    }
+
+    #[test]
+    fn target_area_highest_y() {
+        let target = TargetArea::from_str(SAMPLE).expect("valid input");
+
+        assert_eq!(target.highest_y(), Some(45));
+    }
+}
@@ -1,61 +1,86 @@
-use std::path::PathBuf;
-
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{Day, Inputs};
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    inputs: Inputs,
+
+    /// Step the population back this many days instead, failing when it
+    /// can't have come from a population the day before
+    #[structopt(long)]
+    reverse: Option<usize>,
 }
 
 impl Command {
-    pub fn run(&self) -> Result<()> {
-        let mut population = FishPopulation::parse(
-            read_lines(&self.input)?.iter().map(String::as_ref),
-        )?;
-        for day in 1..=256 {
-            population.next_day();
-            println!("Day {:>2} population: {}", day, population.count());
+    /// Prints the population after every day when `verbosity` is above
+    /// zero. With `--reverse` only the populations of the days before are
+    /// printed.
+    pub fn run(&self, verbosity: u8) -> Result<()> {
+        let population =
+            FishPopulation::parse(self.inputs.lines_iter(SAMPLE)?)?;
+        if let Some(days) = self.reverse {
+            let mut population = population;
+            for day in 1..=days {
+                population.previous_day().with_context(|| {
+                    format!("failed to step back to day -{}", day)
+                })?;
+                println!("Day -{} population: {}", day, population.count());
+            }
+            return Ok(());
         }
+        if verbosity > 0 {
+            let mut population = population.clone();
+            for day in 1..=PART_TWO_DAYS {
+                population.next_day();
+                println!("Day {:>2} population: {}", day, population.count());
+            }
+        }
+        let (part_one, part_two) = population.simulate_parts();
+        println!("Population after 80 days: {}", part_one);
+        println!("Population after 256 days: {}", part_two);
         Ok(())
     }
 }
 
+impl Day for Command {
+    fn part_one(&self, input: &str) -> Result<String> {
+        let population = FishPopulation::parse(input.lines().map(Ok))?;
+        Ok(population.simulate_parts().0.to_string())
+    }
+
+    fn part_two(&self, input: &str) -> Result<String> {
+        let population = FishPopulation::parse(input.lines().map(Ok))?;
+        Ok(population.simulate_parts().1.to_string())
+    }
+}
+
 const FISH_STAGE_COUNT: usize = 9;
+const PART_ONE_DAYS: usize = 80;
+const PART_TWO_DAYS: usize = 256;
 
+#[derive(Clone)]
 struct FishPopulation {
     count_in_reproductive_stage: Vec<u128>,
 }
 
 impl FishPopulation {
-    fn parse<'a, Iter>(input: Iter) -> Result<FishPopulation>
+    fn parse<Iter, Line>(input: Iter) -> Result<FishPopulation>
     where
-        Iter: Iterator<Item = &'a str>,
+        Iter: Iterator<Item = Result<Line>>,
+        Line: AsRef<str>,
     {
-        let count_in_reproductive_stage = input
-            .flat_map(|line| line.split(','))
-            .map(str::trim)
-            .map(|entry| {
-                entry.parse::<u8>().with_context(|| {
+        let mut count_in_reproductive_stage = vec![0u128; FISH_STAGE_COUNT];
+        for line in input {
+            for entry in line?.as_ref().split(',').map(str::trim) {
+                let stage = entry.parse::<u8>().with_context(|| {
                     format!("failed to parse fish stage '{}'", entry)
-                })
-            })
-            .fold(
-                Ok(vec![0u128; FISH_STAGE_COUNT]),
-                |population_result, parse_result| match population_result {
-                    Ok(mut population) => match parse_result {
-                        Ok(fish_stage) => {
-                            population[fish_stage as usize] += 1;
-                            Ok(population)
-                        }
-                        Err(err) => Err(err),
-                    },
-                    Err(err) => Err(err),
-                },
-            )?;
+                })?;
+                count_in_reproductive_stage[stage as usize] += 1;
+            }
+        }
         Ok(FishPopulation {
             count_in_reproductive_stage,
         })
@@ -65,30 +90,111 @@ impl FishPopulation {
         self.count_in_reproductive_stage.iter().sum()
     }
 
+    /// Simulates a copy of the population returning its count after 80 days
+    /// and after 256 days.
+    fn simulate_parts(&self) -> (u128, u128) {
+        let mut population = self.clone();
+        let mut part_one = 0;
+        for day in 1..=PART_TWO_DAYS {
+            population.next_day();
+            if day == PART_ONE_DAYS {
+                part_one = population.count();
+            }
+        }
+        (part_one, population.count())
+    }
+
     fn next_day(&mut self) {
         let ready_to_give_birth = self.count_in_reproductive_stage.remove(0);
         self.count_in_reproductive_stage.push(ready_to_give_birth);
         self.count_in_reproductive_stage[6] += ready_to_give_birth;
     }
+
+    /// Undoes a `next_day` step. Every newborn in stage 8 has a parent that
+    /// was reset to stage 6, so there must be at least as many fish in stage
+    /// 6 as in stage 8.
+    fn previous_day(&mut self) -> Result<()> {
+        let newborns = self.count_in_reproductive_stage[8];
+        let reset = self.count_in_reproductive_stage[6];
+        self.count_in_reproductive_stage[6] =
+            reset.checked_sub(newborns).ok_or_else(|| {
+                anyhow!(
+                    "{} fish in stage 6 can't have given birth to {} fish",
+                    reset,
+                    newborns
+                )
+            })?;
+        let ready_to_give_birth = self
+            .count_in_reproductive_stage
+            .pop()
+            .expect("there are always nine stages");
+        self.count_in_reproductive_stage
+            .insert(0, ready_to_give_birth);
+        Ok(())
+    }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = "3,4,3,1,2\n";
+
 #[cfg(test)]
 mod tests {
-    use super::FishPopulation;
+    use structopt::StructOpt;
+
+    use super::{Command, Day, FishPopulation, SAMPLE};
 
     #[test]
     fn fish_population_parse() {
-        let population = FishPopulation::parse(["3,4,3,1,2"].into_iter())
-            .expect("valid input");
+        let population =
+            FishPopulation::parse(["3,4,3,1,2"].into_iter().map(Ok))
+                .expect("valid input");
 
         assert_eq!(population.count(), 5);
     }
 
     #[test]
-    fn fist_population_next_day() {
-        let mut population = FishPopulation::parse(["3,4,3,1,2"].into_iter())
+    fn fish_population_simulate_parts() {
+        let population =
+            FishPopulation::parse(["3,4,3,1,2"].into_iter().map(Ok))
+                .expect("valid input");
+
+        assert_eq!(population.simulate_parts(), (5934, 26984457539));
+        assert_eq!(population.count(), 5);
+    }
+
+    #[test]
+    fn fish_population_previous_day() {
+        let original = FishPopulation::parse(["3,4,3,1,2"].into_iter().map(Ok))
+            .expect("valid input");
+        let mut population = original.clone();
+
+        for _ in 0..18 {
+            population.next_day();
+        }
+        for _ in 0..18 {
+            population.previous_day().expect("valid successor");
+        }
+
+        assert_eq!(
+            population.count_in_reproductive_stage,
+            original.count_in_reproductive_stage
+        );
+    }
+
+    #[test]
+    fn fish_population_previous_day_invalid_successor() {
+        let mut population = FishPopulation::parse(["8"].into_iter().map(Ok))
             .expect("valid input");
 
+        assert!(population.previous_day().is_err());
+    }
+
+    #[test]
+    fn fist_population_next_day() {
+        let mut population =
+            FishPopulation::parse(["3,4,3,1,2"].into_iter().map(Ok))
+                .expect("valid input");
+
         population.next_day(); // population next day is 2,3,2,0,1
         assert_eq!(population.count(), 5);
 
@@ -114,4 +220,34 @@ mod tests {
         population.next_day(); // population next day is 6,0,6,4,5,6,0,1,1,2,6,7,8,8,8
         assert_eq!(population.count(), 15);
     }
+
+    #[test]
+    fn reverse_option() {
+        let command = Command::from_iter_safe(["six", "--sample"])
+            .expect("valid arguments");
+        assert_eq!(command.reverse, None);
+
+        let command =
+            Command::from_iter_safe(["six", "--sample", "--reverse", "5"])
+                .expect("valid arguments");
+        assert_eq!(command.reverse, Some(5));
+        assert!(command.run(0).is_ok());
+        // six days back a fish in stage 8 has no parent in stage 6
+        let command =
+            Command::from_iter_safe(["six", "--sample", "--reverse", "6"])
+                .expect("valid arguments");
+        assert!(command.run(0).is_err());
+    }
+
+    #[test]
+    fn day_parts() {
+        let command = Command::from_iter_safe(["six", "--sample"])
+            .expect("valid arguments");
+
+        assert_eq!(command.part_one(SAMPLE).expect("valid input"), "5934");
+        assert_eq!(
+            command.part_two(SAMPLE).expect("valid input"),
+            "26984457539"
+        );
+    }
 }
@@ -1,34 +1,53 @@
 use bitvec::{macros::internal::funty::Integral, prelude::*};
-use std::{collections::VecDeque, path::PathBuf};
+use std::collections::HashSet;
 
 type Bits = BitSlice<u8, Msb0>;
 
 use structopt::{self, StructOpt};
 
-use super::read_all_text;
+use super::Input;
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
+
+    #[structopt(long)]
+    bits: bool,
+
+    #[structopt(long, conflicts_with("decode-only"))]
+    version_only: bool,
+
+    #[structopt(long)]
+    decode_only: bool,
 }
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
-        let input = read_all_text(&self.input)?;
+        let input = self.input.text(SAMPLE)?;
+        if self.bits {
+            println!("transmission bits: {}", bit_string(&input)?);
+        }
         let transmission = Transmission::parse(&input)?;
 
-        println!(
-            "transmission package version sum: {}",
-            transmission.version_sum()
-        );
-        println!("transmission package decoded: {}", transmission.decode());
+        if self.version_only {
+            println!("{}", transmission.version_sum());
+        } else if self.decode_only {
+            println!("{}", transmission.decode());
+        } else {
+            println!(
+                "transmission package version sum: {}",
+                transmission.version_sum()
+            );
+            println!("transmission package decoded: {}", transmission.decode());
+        }
         Ok(())
     }
 }
 
+/// A decoded BITS transmission.
 #[derive(Debug)]
-struct Transmission {
+pub struct Transmission {
     package: Package,
 }
 
@@ -94,8 +113,9 @@ enum Package {
     },
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Operation {
+/// What an operator package does with the values of its packages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
     Sum = 0,
     Product = 1,
     Minimum = 2,
@@ -144,6 +164,7 @@ impl Package {
             _ => {
                 let operation = Operation::try_from(type_id)?;
                 let packages = Package::parse_sub_packages(bits)?;
+                operation.check_arity(packages.len())?;
                 Ok(Package::Operator {
                     version,
                     operation,
@@ -210,7 +231,7 @@ impl Package {
 
     fn decode(&self) -> u64 {
         match self {
-            Package::Literal { version: _, value } => *value as u64,
+            Package::Literal { version: _, value } => *value,
             Package::Operator {
                 version: _,
                 operation,
@@ -226,25 +247,31 @@ impl Package {
 }
 
 impl Operation {
+    /// Sum and product treat an empty operand list as their identity, 0 and 1
+    /// respectively. Minimum and maximum have no identity so they need at
+    /// least one operand, and the comparisons need exactly two.
+    fn check_arity(&self, count: usize) -> Result<(), ParseTransmissionError> {
+        let valid = match self {
+            Self::Sum | Self::Product => true,
+            Self::Minimum | Self::Maximum => count >= 1,
+            Self::GreaterThan | Self::LessThan | Self::EqualTo => count == 2,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(ParseTransmissionError::new(&format!(
+                "invalid sub-packet count {count} for operation {self:?}"
+            )))
+        }
+    }
+
     fn execute(&self, values: &[u64]) -> u64 {
         let iter = values.iter();
         match self {
             Self::Sum => iter.sum(),
             Self::Product => iter.product(),
-            Self::Minimum => {
-                if let Some(min) = iter.min() {
-                    *min
-                } else {
-                    0
-                }
-            }
-            Self::Maximum => {
-                if let Some(max) = iter.max() {
-                    *max
-                } else {
-                    0
-                }
-            }
+            Self::Minimum => *iter.min().expect("arity checked on parse"),
+            Self::Maximum => *iter.max().expect("arity checked on parse"),
             Self::GreaterThan => {
                 if values[0] > values[1] {
                     1
@@ -287,36 +314,93 @@ fn bitvec_from_str(
     Ok(bitvector)
 }
 
+/// Spells out the bits of a hexadecimal transmission as `0`s and `1`s.
+pub fn bit_string(input: &str) -> Result<String, ParseTransmissionError> {
+    Ok(bitvec_from_str(input.trim())?
+        .iter()
+        .map(|bit| if *bit { '1' } else { '0' })
+        .collect())
+}
+
 impl Transmission {
-    fn parse(input: &str) -> Result<Self, ParseTransmissionError> {
+    pub fn parse(input: &str) -> Result<Self, ParseTransmissionError> {
         let bitvector = bitvec_from_str(input.trim())?;
         let package = Package::parse(&mut &bitvector[..])?;
         Ok(Transmission { package })
     }
 
-    fn version_sum(&self) -> u64 {
-        let mut version_sum: u64 = 0;
-        let mut pending_packages = VecDeque::from([&self.package]);
-        while let Some(package) = pending_packages.pop_front() {
-            version_sum += match package {
-                Package::Literal { version, value: _ } => *version,
-                Package::Operator {
-                    version,
-                    operation: _,
-                    packages,
-                } => {
-                    for package in packages {
-                        pending_packages.push_back(package);
-                    }
-                    *version
+    pub fn version_sum(&self) -> u64 {
+        self.packages()
+            .map(|(package, _)| match package {
+                Package::Literal { version, .. }
+                | Package::Operator { version, .. } => *version as u64,
+            })
+            .sum()
+    }
+
+    /// Returns how many operators the most deeply nested package is under.
+    pub fn max_depth(&self) -> usize {
+        self.packages().map(|(_, depth)| depth).max().unwrap_or(0)
+    }
+
+    /// Returns the index into each operator's packages leading from the root
+    /// to the first of the most deeply nested packages.
+    pub fn deepest_path(&self) -> Vec<usize> {
+        let mut deepest_path = vec![];
+        let mut pending_packages = vec![(&self.package, vec![])];
+        while let Some((package, path)) = pending_packages.pop() {
+            if path.len() > deepest_path.len() {
+                deepest_path = path.clone();
+            }
+            if let Package::Operator { packages, .. } = package {
+                for (index, package) in packages.iter().enumerate().rev() {
+                    let mut package_path = path.clone();
+                    package_path.push(index);
+                    pending_packages.push((package, package_path));
                 }
-            } as u64;
+            }
         }
 
-        version_sum
+        deepest_path
     }
 
-    fn decode(&self) -> u64 {
+    /// Counts the literal and the operator packages, in that order.
+    pub fn packet_counts(&self) -> (usize, usize) {
+        self.packages().fold(
+            (0, 0),
+            |(literal_count, operator_count), (package, _)| match package {
+                Package::Literal { .. } => (literal_count + 1, operator_count),
+                Package::Operator { .. } => (literal_count, operator_count + 1),
+            },
+        )
+    }
+
+    /// Collects the distinct operations of every operator package.
+    pub fn operations_used(&self) -> HashSet<Operation> {
+        self.packages()
+            .filter_map(|(package, _)| match package {
+                Package::Operator { operation, .. } => Some(*operation),
+                Package::Literal { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Walks every package depth first, along with how many operators it's
+    /// nested under.
+    fn packages(&self) -> impl Iterator<Item = (&Package, usize)> {
+        let mut pending_packages = vec![(&self.package, 0)];
+        std::iter::from_fn(move || {
+            let (package, depth) = pending_packages.pop()?;
+            if let Package::Operator { packages, .. } = package {
+                pending_packages.extend(
+                    packages.iter().rev().map(|package| (package, depth + 1)),
+                );
+            }
+            Some((package, depth))
+        })
+    }
+
+    pub fn decode(&self) -> u64 {
         self.package.decode()
     }
 }
@@ -330,11 +414,53 @@ impl ParseTransmissionError {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = "8A004A801A8002F478\n";
+
 #[cfg(test)]
 mod tests {
+    use bitvec::prelude::*;
+
     use crate::day::sixteen::Operation;
 
-    use super::{bitvec_from_str, Package, Transmission};
+    use super::{bit_string, bitvec_from_str, Package, Transmission};
+
+    #[test]
+    fn test_bit_string_d2fe28() {
+        assert_eq!(
+            "110100101111111000101000",
+            bit_string("D2FE28").expect("valid input")
+        );
+    }
+
+    #[test]
+    fn test_empty_operators() {
+        for (type_id, expected) in [
+            (0u8, Some(0)),
+            (1, Some(1)),
+            (2, None),
+            (3, None),
+            (5, None),
+            (6, None),
+            (7, None),
+        ] {
+            // version 0, the type id, a sub-packet count of 0
+            let mut bitvector = bitvec![u8, Msb0; 0; 3];
+            bitvector.extend(&type_id.view_bits::<Msb0>()[5..]);
+            bitvector.push(true);
+            bitvector.extend(bitvec![u8, Msb0; 0; 11]);
+
+            let package = Package::parse(&mut &bitvector[..]);
+
+            match expected {
+                Some(value) => assert_eq!(
+                    value,
+                    package.expect("identity operation").decode()
+                ),
+                None => assert!(package.is_err()),
+            }
+        }
+    }
 
     #[test]
     pub fn test_d2fe28() {
@@ -508,4 +634,38 @@ mod tests {
 
         assert_eq!(1, transmission.decode());
     }
+
+    #[test]
+    fn test_deepest_path() {
+        let transmission =
+            Transmission::parse("A0016C880162017C3686B18A3D4780")
+                .expect("valid input");
+
+        let path = transmission.deepest_path();
+
+        assert_eq!(transmission.max_depth(), 3);
+        assert_eq!(path.len(), transmission.max_depth());
+        assert_eq!(path, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_packet_counts() {
+        let transmission = Transmission::parse("620080001611562C8802118E34")
+            .expect("valid input");
+
+        assert_eq!(transmission.packet_counts(), (4, 3));
+    }
+
+    #[test]
+    fn test_operations_used() {
+        let transmission = Transmission::parse("9C0141080250320F1802104A08")
+            .expect("valid input");
+
+        let operations = transmission.operations_used();
+
+        assert_eq!(operations.len(), 3);
+        assert!(operations.contains(&Operation::EqualTo));
+        assert!(operations.contains(&Operation::Sum));
+        assert!(operations.contains(&Operation::Product));
+    }
 }
@@ -1,20 +1,103 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
 
+use serde::Serialize;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{Day, Format, Input};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to parse mode from '{0}'")]
 pub struct ParseModeError(String);
 
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to parse points from '{0}'")]
+pub struct ParsePointsError(String);
+
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("detect-corrupted"), long)]
     mode: Mode,
+
+    #[structopt(long)]
+    corrupt_points: Option<PointTable>,
+
+    #[structopt(long)]
+    missing_points: Option<PointTable>,
+}
+
+/// Character point overrides written as `)=3,]=57`.
+#[derive(Clone, Debug, Default)]
+pub struct PointTable(HashMap<char, usize>);
+
+impl FromStr for PointTable {
+    type Err = ParsePointsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let mut chars = entry.chars();
+                match (chars.next(), chars.next(), chars.as_str().parse()) {
+                    (Some(character), Some('='), Ok(points)) => {
+                        Ok((character, points))
+                    }
+                    _ => Err(ParsePointsError(entry.to_owned())),
+                }
+            })
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map(PointTable)
+    }
+}
+
+/// The points scored by corrupted and by missing closing characters.
+#[derive(Clone, Debug)]
+struct Scoring {
+    corrupted: HashMap<char, usize>,
+    missing: HashMap<char, usize>,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        let table = |score: fn(char) -> usize| {
+            [')', ']', '}', '>']
+                .into_iter()
+                .map(|character| (character, score(character)))
+                .collect()
+        };
+        Scoring {
+            corrupted: table(corrupted_character_score),
+            missing: table(missing_character_score),
+        }
+    }
+}
+
+impl Scoring {
+    /// Replaces the default points of any character given in the overrides.
+    fn with_overrides(
+        mut self,
+        corrupted: Option<&PointTable>,
+        missing: Option<&PointTable>,
+    ) -> Self {
+        if let Some(PointTable(corrupted)) = corrupted {
+            self.corrupted.extend(corrupted);
+        }
+        if let Some(PointTable(missing)) = missing {
+            self.missing.extend(missing);
+        }
+        self
+    }
+
+    fn corrupted(&self, character: char) -> usize {
+        self.corrupted.get(&character).copied().unwrap_or(0)
+    }
+
+    fn missing(&self, character: char) -> usize {
+        self.missing.get(&character).copied().unwrap_or(0)
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -36,51 +119,94 @@ impl FromStr for Mode {
 }
 
 impl Command {
-    pub fn run(&self) -> anyhow::Result<()> {
-        let lines = read_lines(&self.input)?;
-
-        match self.mode {
-            Mode::DetectCorrupted => {
-                let points = lines
-                    .iter()
-                    .map(|line| check_syntax(line))
-                    .fold(0, |sum, result| match result {
-                        CheckResult::Corrupted {
-                            expected: _,
-                            found: _,
-                            points,
-                        } => sum + points,
-                        _ => sum,
-                    });
-
-                println!("The total syntax error score is: {}", points);
-            }
-            Mode::Repair => {
-                let mut points = lines
-                    .iter()
-                    .map(|line| check_syntax(line))
-                    .map(|result| match result {
-                        CheckResult::Incomplete {
-                            original: _,
-                            missing: _,
-                            points,
-                        } => Some(points),
-                        _ => None,
-                    })
-                    .filter(Option::is_some)
-                    .map(|points| points.expect("has some"))
-                    .collect::<Vec<usize>>();
-                points.sort_unstable();
-                let mid_points = points[points.len() / 2];
-
-                println!(
-                    "The middle missing characters score is: {}",
-                    mid_points
-                );
-            }
+    pub fn run(&self, format: Format) -> anyhow::Result<()> {
+        let input = self.input.text(SAMPLE)?;
+        let scoring = self.scoring();
+        match (&self.mode, format) {
+            (Mode::DetectCorrupted, Format::Text) => println!(
+                "The total syntax error score is: {}",
+                syntax_error_score(&input, &scoring)
+            ),
+            (Mode::DetectCorrupted, Format::Json) => println!(
+                "{}",
+                serde_json::to_string(&SyntaxErrorScore {
+                    syntax_error_score: syntax_error_score(&input, &scoring),
+                })?
+            ),
+            (Mode::Repair, Format::Text) => println!(
+                "The middle missing characters score is: {}",
+                middle_missing_score(&input, &scoring)?
+            ),
+            (Mode::Repair, Format::Json) => println!(
+                "{}",
+                serde_json::to_string(&MiddleMissingScore {
+                    middle_missing_score: middle_missing_score(
+                        &input, &scoring
+                    )?,
+                })?
+            ),
         }
         Ok(())
     }
+
+    fn scoring(&self) -> Scoring {
+        Scoring::default().with_overrides(
+            self.corrupt_points.as_ref(),
+            self.missing_points.as_ref(),
+        )
+    }
+}
+
+impl Day for Command {
+    /// The total syntax error score of the corrupted lines.
+    fn part_one(&self, input: &str) -> anyhow::Result<String> {
+        Ok(syntax_error_score(input, &self.scoring()).to_string())
+    }
+
+    /// The middle score of the characters missing from incomplete lines.
+    fn part_two(&self, input: &str) -> anyhow::Result<String> {
+        Ok(middle_missing_score(input, &self.scoring())?.to_string())
+    }
+}
+
+/// The `--format json` answer in detect corrupted mode.
+#[derive(Debug, Serialize)]
+struct SyntaxErrorScore {
+    syntax_error_score: usize,
+}
+
+/// The `--format json` answer in repair mode.
+#[derive(Debug, Serialize)]
+struct MiddleMissingScore {
+    middle_missing_score: usize,
+}
+
+fn syntax_error_score(input: &str, scoring: &Scoring) -> usize {
+    input
+        .lines()
+        .map(|line| match check_syntax_scored(line, scoring) {
+            CheckResult::Corrupted { points, .. } => points,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn middle_missing_score(
+    input: &str,
+    scoring: &Scoring,
+) -> anyhow::Result<usize> {
+    let mut points = input
+        .lines()
+        .filter_map(|line| match check_syntax_scored(line, scoring) {
+            CheckResult::Incomplete { points, .. } => Some(points),
+            _ => None,
+        })
+        .collect::<Vec<usize>>();
+    points.sort_unstable();
+    points
+        .get(points.len() / 2)
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("there are no incomplete lines"))
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -101,7 +227,7 @@ enum CheckResult {
 
 impl std::cmp::Eq for CheckResult {}
 
-fn check_syntax(line: &str) -> CheckResult {
+fn check_syntax_scored(line: &str, scoring: &Scoring) -> CheckResult {
     let mut state = vec![];
     for character in line.chars() {
         match character {
@@ -112,7 +238,7 @@ fn check_syntax(line: &str) -> CheckResult {
                     return CheckResult::Corrupted {
                         expected,
                         found: character,
-                        points: corrupted_character_score(character),
+                        points: scoring.corrupted(character),
                     }
                 }
             },
@@ -127,7 +253,7 @@ fn check_syntax(line: &str) -> CheckResult {
             (String::with_capacity(state.len()), 0),
             |(mut missing, points), character| {
                 missing.push(*character);
-                (missing, points * 5 + missing_character_score(*character))
+                (missing, points * 5 + scoring.missing(*character))
             },
         );
         CheckResult::Incomplete {
@@ -168,11 +294,34 @@ fn close_character(character: char) -> char {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+";
+
 #[cfg(test)]
 mod tests {
-    use super::{check_syntax, corrupted_character_score, CheckResult};
+    use structopt::StructOpt;
+
+    use super::{
+        check_syntax_scored, corrupted_character_score, CheckResult, Command,
+        Day, PointTable, Scoring, SAMPLE,
+    };
     use lazy_static::lazy_static;
 
+    /// Checks `line` with the puzzle's own scoring tables.
+    fn check_syntax(line: &str) -> CheckResult {
+        check_syntax_scored(line, &Scoring::default())
+    }
+
     #[test]
     fn illegal_character_score_test() {
         let test_cases = [(')', 3), (']', 57), ('}', 1197), ('>', 25137)];
@@ -206,6 +355,26 @@ mod tests {
         assert_eq!(points, 26397);
     }
 
+    #[test]
+    fn check_syntax_scored_overrides_points_test() {
+        let overrides = ")=10".parse::<PointTable>().expect("valid points");
+        let scoring = Scoring::default().with_overrides(Some(&overrides), None);
+        let lines = ["(]", "{()()()>", "(((()))}", "<([]){()}[{}])"];
+        let total = |scoring: &Scoring| {
+            lines
+                .iter()
+                .map(|line| match check_syntax_scored(line, scoring) {
+                    CheckResult::Corrupted { points, .. } => points,
+                    _ => 0,
+                })
+                .sum::<usize>()
+        };
+
+        assert_eq!(total(&Scoring::default()), 57 + 25137 + 1197 + 3);
+        assert_eq!(total(&scoring), 57 + 25137 + 1197 + 10);
+        assert!("]57".parse::<PointTable>().is_err());
+    }
+
     #[test]
     fn check_syntax_missing_characters_points_test() {
         let mut points = TEST_CASES
@@ -225,6 +394,63 @@ mod tests {
         points.sort();
         assert_eq!(points[points.len() / 2], 288957);
     }
+    #[test]
+    fn check_syntax_balanced_lines_property_test() {
+        let mut rng = XorShift(0x2021_1210);
+        for _ in 0..500 {
+            let line = balanced_line(&mut rng, 4);
+            assert_eq!(check_syntax(&line), CheckResult::Valid, "{}", line);
+
+            let stray = CLOSE[rng.below(CLOSE.len())];
+            let corrupted_line = format!("{}{}", line, stray);
+            assert!(
+                matches!(
+                    check_syntax(&corrupted_line),
+                    CheckResult::Corrupted {
+                        expected: None,
+                        found,
+                        points: _,
+                    } if found == stray
+                ),
+                "{}",
+                corrupted_line
+            );
+        }
+    }
+
+    const OPEN: [char; 4] = ['(', '[', '{', '<'];
+    const CLOSE: [char; 4] = [')', ']', '}', '>'];
+
+    /// Small deterministic xorshift generator so the property test is
+    /// repeatable.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    fn balanced_line(rng: &mut XorShift, depth: usize) -> String {
+        let mut line = String::new();
+        for _ in 0..rng.below(4) {
+            let bracket = rng.below(OPEN.len());
+            line.push(OPEN[bracket]);
+            if depth > 0 {
+                line.push_str(&balanced_line(rng, depth - 1));
+            }
+            line.push(CLOSE[bracket]);
+        }
+        line
+    }
+
     struct TestCase {
         input: &'static str,
         expected: CheckResult,
@@ -298,4 +524,13 @@ mod tests {
             TestCase::incomplete("<{([{{}}[<[[[<>{}]]]>[]]", "])}>", 294),
         ];
     }
+
+    #[test]
+    fn day_parts() {
+        let command = Command::from_iter_safe(["ten", "--sample"])
+            .expect("valid arguments");
+
+        assert_eq!(command.part_one(SAMPLE).expect("valid input"), "26397");
+        assert_eq!(command.part_two(SAMPLE).expect("valid input"), "288957");
+    }
 }
@@ -1,13 +1,12 @@
 use std::{
     cmp::{self, Ordering},
     fmt::Display,
-    path::PathBuf,
     str::FromStr,
 };
 
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{geometry::Point, Input};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to parse mode from '{0}'")]
@@ -15,8 +14,8 @@ pub struct ParseModeError(String);
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("fold-one-count"), long)]
     mode: Mode,
@@ -52,27 +51,24 @@ impl Display for Mode {
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
         let mut transparency = Transparency::parse(
-            read_lines(&self.input)?.iter().map(String::as_ref),
+            self.input.lines(SAMPLE)?.iter().map(String::as_ref),
         )?;
         match self.mode {
             Mode::FoldOneCount => {
-                transparency.fold();
-                println!(
-                    "Dots after one fold: {}",
-                    transparency.dots().count()
-                );
+                let count = transparency
+                    .fold_counted()
+                    .map_or(transparency.dots().count(), |(_, count)| count);
+                println!("Dots after one fold: {}", count);
             }
             Mode::FoldAllRender => {
                 while transparency.fold().is_some() {}
-                let mut grid = vec![
-                    vec!['.'; transparency.height()];
-                    transparency.width()
-                ];
-                for dot in transparency.dots() {
-                    grid[dot.x][dot.y] = '#';
-                }
-                for row in grid {
-                    println!("{}", row.iter().collect::<String>());
+                for row in transparency.to_matrix() {
+                    println!(
+                        "{}",
+                        row.iter()
+                            .map(|dot| if *dot { '#' } else { '.' })
+                            .collect::<String>()
+                    );
                 }
             }
         }
@@ -97,11 +93,7 @@ struct Transparency {
     applied_folds: Vec<Fold>,
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Dot {
-    x: usize,
-    y: usize,
-}
+type Dot = Point<usize>;
 
 #[derive(Clone, Copy, Debug)]
 enum Fold {
@@ -110,11 +102,24 @@ enum Fold {
 }
 
 const FOLD_ALONG: &str = "fold along ";
+const DEFAULT_MAX_COORDINATE: usize = 10000;
 
 impl Transparency {
     fn parse<'iter, Iter>(
         lines: Iter,
     ) -> Result<Transparency, ParseTransparencyError>
+    where
+        Iter: Iterator<Item = &'iter str>,
+    {
+        Transparency::parse_bounded(lines, DEFAULT_MAX_COORDINATE)
+    }
+
+    /// Parses the transparency rejecting dots with a coordinate greater than
+    /// `max_coordinate` so rendering doesn't allocate an unbounded grid.
+    fn parse_bounded<'iter, Iter>(
+        lines: Iter,
+        max_coordinate: usize,
+    ) -> Result<Transparency, ParseTransparencyError>
     where
         Iter: Iterator<Item = &'iter str>,
     {
@@ -132,12 +137,20 @@ impl Transparency {
                 folds.push(Fold::parse(text)?)
             } else {
                 let dot = Dot::parse(line)?;
+                let coordinate = std::cmp::max(dot.x, dot.y);
+                if coordinate > max_coordinate {
+                    return Err(parse_error(&format!(
+                        "coordinate {} in '{}' is over the limit of {}",
+                        coordinate, line, max_coordinate
+                    )));
+                }
                 height = std::cmp::max(dot.y, height);
                 width = std::cmp::max(dot.x, width);
                 dots.push(dot);
             }
         }
         dots.sort_unstable();
+        dots.dedup();
 
         Ok(Transparency {
             dots,
@@ -160,6 +173,20 @@ impl Transparency {
         self.dots.iter()
     }
 
+    /// The dots are kept sorted and deduplicated so this is just their count.
+    fn unique_dot_count(&self) -> usize {
+        self.dots.len()
+    }
+
+    /// Returns the `height` rows of `width` cells marking where dots are.
+    fn to_matrix(&self) -> Vec<Vec<bool>> {
+        let mut matrix = vec![vec![false; self.width()]; self.height()];
+        for dot in self.dots() {
+            matrix[dot.y][dot.x] = true;
+        }
+        matrix
+    }
+
     #[allow(dead_code)]
     fn pending_folds(&self) -> impl Iterator<Item = &Fold> {
         self.pending_folds.iter()
@@ -184,12 +211,21 @@ impl Transparency {
         }
     }
 
+    /// Applies the next fold returning it along with the remaining dot count.
+    fn fold_counted(&mut self) -> Option<(Fold, usize)> {
+        self.fold().map(|fold| (fold, self.unique_dot_count()))
+    }
+
     fn fold_horizontal(&mut self, value: usize) {
         self.height = 0;
-        for dot in self.dots.iter_mut() {
-            if dot.y > value {
-                dot.y = value - (dot.y - value);
-            }
+        self.dots = self
+            .dots
+            .iter()
+            .filter_map(|dot| {
+                fold_coordinate(dot.y, value).map(|y| Dot { x: dot.x, y })
+            })
+            .collect();
+        for dot in self.dots.iter() {
             self.height = cmp::max(dot.y, self.height);
         }
         self.height += 1;
@@ -199,10 +235,14 @@ impl Transparency {
 
     fn fold_vertical(&mut self, value: usize) {
         self.width = 0;
-        for dot in self.dots.iter_mut() {
-            if dot.x > value {
-                dot.x = value - (dot.x - value);
-            }
+        self.dots = self
+            .dots
+            .iter()
+            .filter_map(|dot| {
+                fold_coordinate(dot.x, value).map(|x| Dot { x, y: dot.y })
+            })
+            .collect();
+        for dot in self.dots.iter() {
             self.width = cmp::max(dot.x, self.width);
         }
         self.width += 1;
@@ -211,8 +251,18 @@ impl Transparency {
     }
 }
 
+/// Mirrors `coordinate` across the fold `line`. Dots on the fold line
+/// disappear, as do dots that would be mirrored past the origin.
+fn fold_coordinate(coordinate: usize, line: usize) -> Option<usize> {
+    match coordinate.cmp(&line) {
+        Ordering::Less => Some(coordinate),
+        Ordering::Equal => None,
+        Ordering::Greater => line.checked_sub(coordinate - line),
+    }
+}
+
 impl Dot {
-    fn parse(text: &str) -> Result<Dot, ParseTransparencyError> {
+    fn parse(text: &str) -> Result<Self, ParseTransparencyError> {
         let text = text.trim();
         if let Some(index) = text.find(',') {
             if let (Ok(x), Ok(y)) = (
@@ -226,38 +276,6 @@ impl Dot {
     }
 }
 
-impl Display for Dot {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
-
-impl PartialEq for Dot {
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y
-    }
-}
-
-impl Eq for Dot {}
-
-impl PartialOrd for Dot {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.x.partial_cmp(&other.x) {
-            Some(Ordering::Equal) => self.y.partial_cmp(&other.y),
-            ord => ord,
-        }
-    }
-}
-
-impl Ord for Dot {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match self.x.cmp(&other.x) {
-            Ordering::Equal => self.y.cmp(&other.y),
-            ord => ord,
-        }
-    }
-}
-
 impl Fold {
     fn parse(text: &str) -> Result<Fold, ParseTransparencyError> {
         let index = text.rfind('=').ok_or_else(|| parse_error(text))?;
@@ -267,10 +285,14 @@ impl Fold {
         let value = text[(index + 1)..]
             .parse::<usize>()
             .map_err(|_| parse_error(text))?;
-        match &text[(index - 1)..index] {
+        match text[(index - 1)..index].to_lowercase().as_ref() {
             "y" => Ok(Fold::Horizontal(value)),
             "x" => Ok(Fold::Vertical(value)),
-            _ => Err(parse_error(text)),
+            _ => Err(parse_error(&format!(
+                "unknown fold axis '{}' in '{}'",
+                &text[(index - 1)..index],
+                text
+            ))),
         }
     }
 }
@@ -284,26 +306,82 @@ impl Display for Fold {
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5";
+
 #[cfg(test)]
 mod tests {
-    use super::{Dot, Transparency};
+    use super::{Dot, Fold, Transparency, SAMPLE};
 
     #[test]
     fn transparency_parse() {
         let transparency =
-            Transparency::parse(INPUT.split("\n")).expect("valid input");
+            Transparency::parse(SAMPLE.split("\n")).expect("valid input");
 
         assert_eq!(transparency.dots().count(), 18);
-        assert_eq!(transparency.width(), 10);
-        assert_eq!(transparency.height(), 14);
+        assert_eq!(transparency.width(), 11);
+        assert_eq!(transparency.height(), 15);
         assert_eq!(transparency.pending_folds().count(), 2);
         assert_eq!(transparency.applied_folds().count(), 0);
     }
 
+    #[test]
+    fn transparency_unique_dot_count() {
+        let transparency =
+            Transparency::parse(["6,10", "0,14", "6,10"].into_iter())
+                .expect("valid input");
+
+        assert_eq!(transparency.unique_dot_count(), 2);
+        assert_eq!(transparency.dots().count(), 2);
+    }
+
+    #[test]
+    fn transparency_parse_rejects_out_of_range_dot() {
+        let error = Transparency::parse(["100000,0"].into_iter())
+            .expect_err("out of range dot");
+        assert!(error.to_string().contains(
+            "coordinate 100000 in '100000,0' is over the limit of 10000"
+        ));
+        assert!(Transparency::parse(["6,10", "0,14"].into_iter()).is_ok());
+        assert!(
+            Transparency::parse_bounded(["6,10", "0,14"].into_iter(), 12)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn fold_parse_axis() {
+        assert!(matches!(Fold::parse("Y=5"), Ok(Fold::Horizontal(5))));
+        assert!(matches!(Fold::parse("X=3"), Ok(Fold::Vertical(3))));
+
+        let error = Fold::parse("z=5").expect_err("invalid axis");
+        assert!(error.to_string().contains("axis 'z'"));
+    }
+
     #[test]
     fn transparency_fold() {
         let mut transparency =
-            Transparency::parse(INPUT.split("\n")).expect("valid input");
+            Transparency::parse(SAMPLE.split("\n")).expect("valid input");
 
         transparency.fold();
 
@@ -315,10 +393,26 @@ mod tests {
             .all(|(expected, actual)| expected == *actual));
     }
 
+    #[test]
+    fn transparency_fold_counted() {
+        let mut transparency =
+            Transparency::parse(SAMPLE.split("\n")).expect("valid input");
+
+        assert!(matches!(
+            transparency.fold_counted(),
+            Some((Fold::Horizontal(7), 17))
+        ));
+        assert!(matches!(
+            transparency.fold_counted(),
+            Some((Fold::Vertical(5), 16))
+        ));
+        assert!(transparency.fold_counted().is_none());
+    }
+
     #[test]
     fn transparency_fold_second() {
         let mut transparency =
-            Transparency::parse(INPUT.split("\n")).expect("valid input");
+            Transparency::parse(SAMPLE.split("\n")).expect("valid input");
 
         transparency.fold();
         transparency.fold();
@@ -328,33 +422,37 @@ mod tests {
             .split('\n')
             .map(|text| Dot::parse(text).expect("valid imput"))
             .zip(transparency.dots())
-            .all(|(expected, actual)| {
-                let result = expected == *actual;
-                result
-            }));
+            .all(|(expected, actual)| { expected == *actual }));
     }
 
-    const INPUT: &str = r"6,10
-0,14
-9,10
-0,3
-10,4
-4,11
-6,0
-6,12
-4,1
-0,13
-10,12
-3,4
-3,0
-8,4
-1,10
-2,14
-8,10
-9,0
+    #[test]
+    fn transparency_fold_drops_dots_on_fold_line() {
+        let mut transparency = Transparency::parse(
+            ["1,7", "2,3", "4,16", "fold along y=7"].into_iter(),
+        )
+        .expect("valid input");
 
-fold along y=7
-fold along x=5";
+        transparency.fold();
+
+        assert_eq!(
+            transparency.dots().copied().collect::<Vec<_>>(),
+            vec![Dot { x: 2, y: 3 }]
+        );
+    }
+
+    #[test]
+    fn transparency_to_matrix() {
+        let mut transparency =
+            Transparency::parse(SAMPLE.split("\n")).expect("valid input");
+
+        while transparency.fold().is_some() {}
+        let matrix = transparency.to_matrix();
+
+        assert_eq!(matrix.len(), transparency.height());
+        assert!(matrix.iter().all(|row| row.len() == transparency.width()));
+        assert_eq!(matrix.iter().flatten().filter(|dot| **dot).count(), 16);
+        assert_eq!(matrix[2], vec![true, false, false, false, true]);
+    }
 
     const EXPECTED_FIRST_FOLD_DOTS: &str = r"0,0
 0,1
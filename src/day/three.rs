@@ -1,31 +1,58 @@
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
+use super::{mode::mode_enum, Inputs};
 
-const POWER_CONSUMPTION: &str = "power-consumption";
-const LIFE_SUPPORT: &str = "life-support";
+mode_enum! {
+    pub enum System {
+        PowerConsumption => "power-consumption",
+        LifeSupport => "life-support",
+    }
+}
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    inputs: Inputs,
 
     #[structopt(long)]
-    system: String,
+    system: System,
 }
 
+/// The rates and ratings are held in a `u64`.
+const MAX_BIT_COUNT: usize = 64;
+
 struct MeasureStats {
     set_bit_counts: Vec<usize>,
     count: usize,
 }
 
 fn get_measure_stats(lines: &[&str]) -> Result<MeasureStats> {
+    stream_measure_stats(lines.iter().map(Ok))
+}
+
+/// Accumulates the measure stats one line at a time so the lines don't need
+/// to be collected up front.
+fn stream_measure_stats<Iter, Line>(lines: Iter) -> Result<MeasureStats>
+where
+    Iter: Iterator<Item = Result<Line>>,
+    Line: AsRef<str>,
+{
     let mut set_bit_counts: Vec<usize> = Vec::new();
     let mut count = 0;
     for line in lines {
+        let line = line?;
+        let line = line.as_ref();
+        if line.is_empty() {
+            return Err(anyhow!("measurement {} is empty", count + 1));
+        }
         if count == 0 {
+            if line.len() > MAX_BIT_COUNT {
+                return Err(anyhow!(
+                    "measurements wider than {} bits aren't supported",
+                    MAX_BIT_COUNT
+                ));
+            }
             set_bit_counts.extend(vec![0; line.len()]);
         } else if set_bit_counts.len() != line.len() {
             return Err(anyhow!(
@@ -49,6 +76,9 @@ fn get_measure_stats(lines: &[&str]) -> Result<MeasureStats> {
 
         count += 1;
     }
+    if count == 0 {
+        return Err(anyhow!("no measurements to diagnose"));
+    }
 
     Ok(MeasureStats {
         set_bit_counts,
@@ -58,115 +88,315 @@ fn get_measure_stats(lines: &[&str]) -> Result<MeasureStats> {
 
 impl Command {
     pub fn run(&self) -> Result<()> {
-        let owned_lines = read_lines(&self.input)?;
-        let lines: Vec<&str> = owned_lines.iter().map(String::as_str).collect();
-        match self.system.as_ref() {
-            POWER_CONSUMPTION => self.calc_power_consumption(&lines),
-            LIFE_SUPPORT => self.calc_life_support(&lines),
-            _ => Err(anyhow!(format!("unknown system '{}'", &self.system))),
+        match self.system {
+            System::PowerConsumption => self.calc_power_consumption(),
+            System::LifeSupport => {
+                let owned_lines = self.inputs.lines(SAMPLE)?;
+                let lines: Vec<&str> =
+                    owned_lines.iter().map(String::as_str).collect();
+                self.calc_life_support(&lines)
+            }
         }
     }
 
-    fn calc_power_consumption(&self, lines: &[&str]) -> Result<()> {
-        let stats = get_measure_stats(lines)?;
-        let majority =
-            stats.count / 2 + if stats.count % 2 == 1 { 1 } else { 0 };
-        let mut gamma_rate: usize = 0;
-        let mut epsilon_rate: usize = 0;
-        for set_bit_count in stats.set_bit_counts {
-            gamma_rate = (gamma_rate << 1)
-                + if set_bit_count >= majority { 1 } else { 0 };
-            epsilon_rate = (epsilon_rate << 1)
-                + if set_bit_count < majority { 1 } else { 0 };
-        }
-
+    fn calc_power_consumption(&self) -> Result<()> {
+        let stats = stream_measure_stats(self.inputs.lines_iter(SAMPLE)?)?;
+        let (gamma_rate, epsilon_rate) = rates(&stats);
         println!(
             "Gamma rate: {}, Epsilon rate: {}, Measure: {}",
             gamma_rate,
             epsilon_rate,
-            gamma_rate * epsilon_rate
+            product(gamma_rate, epsilon_rate)?
         );
 
         Ok(())
     }
 
     fn calc_life_support(&self, lines: &[&str]) -> Result<()> {
-        let mut oxygen_rating: Vec<&str> = Vec::new();
-        oxygen_rating.extend(lines);
-        let mut scrubber_rating = oxygen_rating.clone();
-
-        let mut index = 0;
-        loop {
-            let stats = get_measure_stats(&oxygen_rating)?;
-            let majority =
-                stats.count / 2 + if stats.count % 2 == 1 { 1 } else { 0 };
-            let majority_value = if stats.set_bit_counts[index] >= majority {
-                '1'
-            } else {
-                '0'
-            };
-            oxygen_rating = oxygen_rating
-                .into_iter()
-                .filter(|measure| {
-                    measure
-                        .chars()
-                        .nth(index)
-                        .expect("already validated length")
-                        == majority_value
-                })
-                .collect();
-            if oxygen_rating.len() == 1 {
-                break;
-            }
-            index += 1;
-        }
+        let (oxygen_rating, scrubber_rating) = life_support_ratings(lines)?;
+        let width = lines.first().map_or(0, |line| line.len());
+        println!(
+            "{:0width$b}({}) : {:0width$b}({}) [{}]",
+            oxygen_rating,
+            oxygen_rating,
+            scrubber_rating,
+            scrubber_rating,
+            product(oxygen_rating, scrubber_rating)?,
+            width = width
+        );
 
-        index = 0;
-        loop {
-            let stats = get_measure_stats(&scrubber_rating)?;
-            let majority =
-                stats.count / 2 + if stats.count % 2 == 1 { 1 } else { 0 };
-            let minority_value = if stats.set_bit_counts[index] < majority {
-                '1'
-            } else {
-                '0'
-            };
-            scrubber_rating = scrubber_rating
-                .into_iter()
-                .filter(|measure| {
-                    measure
-                        .chars()
-                        .nth(index)
-                        .expect("already validated length")
-                        == minority_value
-                })
-                .collect();
-            if scrubber_rating.len() == 1 {
-                break;
-            }
-            index += 1;
+        Ok(())
+    }
+}
+
+/// The gamma and epsilon rates of the measurements.
+pub fn gamma_epsilon(lines: &[&str]) -> Result<(u64, u64)> {
+    Ok(rates(&get_measure_stats(lines)?))
+}
+
+/// The gamma rate takes the most common bit at each position and the
+/// epsilon rate the least common one.
+fn rates(stats: &MeasureStats) -> (u64, u64) {
+    let majority = stats.count / 2 + if stats.count % 2 == 1 { 1 } else { 0 };
+    let mut gamma_rate: u64 = 0;
+    let mut epsilon_rate: u64 = 0;
+    for set_bit_count in &stats.set_bit_counts {
+        gamma_rate =
+            (gamma_rate << 1) + if *set_bit_count >= majority { 1 } else { 0 };
+        epsilon_rate =
+            (epsilon_rate << 1) + if *set_bit_count < majority { 1 } else { 0 };
+    }
+    (gamma_rate, epsilon_rate)
+}
+
+/// Multiplies two rates or ratings, failing rather than wrapping when the
+/// measurements are wide enough for the product to overflow a `u64`.
+fn product(first: u64, second: u64) -> Result<u64> {
+    first.checked_mul(second).ok_or_else(|| {
+        anyhow!("the product of {} and {} overflows a u64", first, second)
+    })
+}
+
+/// The oxygen generator and CO2 scrubber ratings.
+pub fn life_support_ratings(lines: &[&str]) -> Result<(u64, u64)> {
+    let matrix = BitMatrix::parse(lines)?;
+    Ok((
+        find_rating(&matrix, BitCriteria::MostCommon),
+        find_rating(&matrix, BitCriteria::LeastCommon),
+    ))
+}
+
+/// The measurements parsed once into rows of bits so that filtering the
+/// candidates indexes a bit rather than re-reading every line.
+struct BitMatrix {
+    rows: Vec<u64>,
+    width: usize,
+}
+
+impl BitMatrix {
+    /// Checks every measurement before packing each into a row, the first
+    /// character being the most significant bit.
+    fn parse(lines: &[&str]) -> Result<Self> {
+        let stats = get_measure_stats(lines)?;
+        let rows = lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .fold(0, |row, char| (row << 1) | u64::from(char == '1'))
+            })
+            .collect();
+        Ok(BitMatrix {
+            rows,
+            width: stats.set_bit_counts.len(),
+        })
+    }
+
+    /// Whether `row` has a `1` at `index`, counting from the left.
+    fn is_set(&self, row: u64, index: usize) -> bool {
+        (row >> (self.width - 1 - index)) & 1 == 1
+    }
+}
+
+/// Which bit value the candidates must have at each position to stay in the
+/// running for a rating. On a tie the oxygen generator's most common bit is
+/// `1` and the CO2 scrubber's least common bit is `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BitCriteria {
+    MostCommon,
+    LeastCommon,
+}
+
+impl BitCriteria {
+    /// Whether the candidates with a `1` at the position are kept.
+    fn keep_set(&self, set_bit_count: usize, count: usize) -> bool {
+        // a tie counts as `1` being the most common bit
+        let ones_most_common = set_bit_count * 2 >= count;
+        match self {
+            BitCriteria::MostCommon => ones_most_common,
+            BitCriteria::LeastCommon => !ones_most_common,
         }
+    }
+}
 
-        if oxygen_rating.len() == 1 && scrubber_rating.len() == 1 {
-            let oxygen_rating_str = oxygen_rating[0];
-            let oxygen_rating =
-                i32::from_str_radix(oxygen_rating_str, 2).unwrap();
-            let scrubber_rating_str = scrubber_rating[0];
-            let scrubber_rating =
-                i32::from_str_radix(scrubber_rating_str, 2).unwrap();
-            let measure = oxygen_rating * scrubber_rating;
-            println!(
-                "{}({}) : {}({}) [{}]",
-                oxygen_rating_str,
-                oxygen_rating,
-                scrubber_rating_str,
-                scrubber_rating,
-                measure
-            );
-        } else {
-            println!("wtf");
+/// Filters the candidates a bit position at a time, from the left, until
+/// one is left. Only the bit width's worth of positions are considered, so
+/// duplicate rows that can't be told apart end with the first of them, and a
+/// position where every candidate has the same bit filters nothing out.
+fn find_rating(matrix: &BitMatrix, criteria: BitCriteria) -> u64 {
+    let mut candidates = matrix.rows.clone();
+    for index in 0..matrix.width {
+        if candidates.len() <= 1 {
+            break;
+        }
+        let set_bit_count = candidates
+            .iter()
+            .filter(|row| matrix.is_set(**row, index))
+            .count();
+        let keep_set = criteria.keep_set(set_bit_count, candidates.len());
+        let bit_matches = |row: &u64| matrix.is_set(*row, index) == keep_set;
+        if candidates.iter().any(bit_matches) {
+            candidates.retain(bit_matches);
         }
+    }
+    // parsing rejects empty input and filtering never removes every row
+    candidates[0]
+}
 
-        Ok(())
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010";
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use super::{
+        find_rating, gamma_epsilon, get_measure_stats, life_support_ratings,
+        product, stream_measure_stats, BitCriteria, BitMatrix, Command, System,
+        SAMPLE,
+    };
+
+    #[test]
+    fn system_is_parsed_with_the_arguments() {
+        let command = Command::from_iter_safe([
+            "three",
+            "--sample",
+            "--system",
+            "life-support",
+        ])
+        .expect("valid arguments");
+
+        assert_eq!(command.system, System::LifeSupport);
+        assert!(Command::from_iter_safe([
+            "three",
+            "--sample",
+            "--system",
+            "life-suport"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn stream_measure_stats_matches_slice() {
+        let lines = SAMPLE.split('\n').collect::<Vec<&str>>();
+
+        let stats = get_measure_stats(&lines).expect("valid input");
+        let streamed_stats = stream_measure_stats(
+            SAMPLE.split('\n').map(str::to_owned).map(anyhow::Ok),
+        )
+        .expect("valid input");
+
+        assert_eq!(stats.set_bit_counts, streamed_stats.set_bit_counts);
+        assert_eq!(stats.count, streamed_stats.count);
+    }
+
+    #[test]
+    fn gamma_epsilon_test() {
+        let lines = SAMPLE.split('\n').collect::<Vec<&str>>();
+
+        let (gamma_rate, epsilon_rate) =
+            gamma_epsilon(&lines).expect("valid input");
+        assert_eq!((gamma_rate, epsilon_rate), (22, 9));
+        assert_eq!(gamma_rate * epsilon_rate, 198);
+    }
+
+    #[test]
+    fn life_support_ratings_test() {
+        let lines = SAMPLE.split('\n').collect::<Vec<&str>>();
+
+        let (oxygen_rating, scrubber_rating) =
+            life_support_ratings(&lines).expect("valid input");
+        assert_eq!((oxygen_rating, scrubber_rating), (23, 10));
+        assert_eq!(oxygen_rating * scrubber_rating, 230);
+    }
+
+    #[test]
+    fn wide_measurements() {
+        let lines = [
+            "1111111111111111111111111111111111111110",
+            "1111111111111111111111111111111111111101",
+            "0000000000000000000000000000000000000011",
+        ];
+
+        let (gamma_rate, epsilon_rate) =
+            gamma_epsilon(&lines).expect("valid input");
+        assert_eq!((gamma_rate, epsilon_rate), ((1 << 40) - 1, 0));
+        assert_eq!(
+            life_support_ratings(&lines).expect("valid input"),
+            ((1 << 40) - 2, 3)
+        );
+        assert_eq!(
+            product((1 << 40) - 2, 3).expect("fits a u64"),
+            (1 << 41) * 3 / 2 - 6
+        );
+        assert!(product((1 << 40) - 1, (1 << 40) - 1).is_err());
+        assert!(get_measure_stats(&["1".repeat(65).as_str()]).is_err());
+    }
+
+    #[test]
+    fn empty_measurements_are_rejected() {
+        assert_eq!(
+            get_measure_stats(&[]).err().map(|err| err.to_string()),
+            Some("no measurements to diagnose".to_owned())
+        );
+        assert!(gamma_epsilon(&[]).is_err());
+        assert_eq!(
+            life_support_ratings(&[]).err().map(|err| err.to_string()),
+            Some("no measurements to diagnose".to_owned())
+        );
+        assert_eq!(
+            get_measure_stats(&["101", ""])
+                .err()
+                .map(|err| err.to_string()),
+            Some("measurement 2 is empty".to_owned())
+        );
+        assert!(life_support_ratings(&[""]).is_err());
+    }
+
+    #[test]
+    fn bit_matrix_parse() {
+        let lines = SAMPLE.split('\n').collect::<Vec<&str>>();
+        let matrix = BitMatrix::parse(&lines).expect("valid input");
+
+        assert_eq!(matrix.width, 5);
+        assert_eq!(matrix.rows.len(), 12);
+        assert_eq!(matrix.rows[..3], [0b00100, 0b11110, 0b10110]);
+        assert!(matrix.is_set(0b10000, 0));
+        assert!(!matrix.is_set(0b10000, 4));
+        assert!(BitMatrix::parse(&["101", "10"]).is_err());
+    }
+
+    #[test]
+    fn find_rating_test() {
+        let lines = SAMPLE.split('\n').collect::<Vec<&str>>();
+        let matrix = BitMatrix::parse(&lines).expect("valid input");
+
+        assert_eq!(find_rating(&matrix, BitCriteria::MostCommon), 0b10111);
+        assert_eq!(find_rating(&matrix, BitCriteria::LeastCommon), 0b01010);
+    }
+
+    #[test]
+    fn find_rating_ties_and_duplicates() {
+        let ties = BitMatrix::parse(&["10", "01"]).expect("valid input");
+        assert_eq!(find_rating(&ties, BitCriteria::MostCommon), 0b10);
+        assert_eq!(find_rating(&ties, BitCriteria::LeastCommon), 0b01);
+
+        let duplicates =
+            BitMatrix::parse(&["101", "101", "011"]).expect("valid input");
+        assert_eq!(find_rating(&duplicates, BitCriteria::MostCommon), 0b101);
+        assert_eq!(find_rating(&duplicates, BitCriteria::LeastCommon), 0b011);
+
+        let same = BitMatrix::parse(&["110", "110"]).expect("valid input");
+        assert_eq!(find_rating(&same, BitCriteria::LeastCommon), 0b110);
     }
 }
@@ -1,7 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Display},
-    path::PathBuf,
     result,
     str::FromStr,
 };
@@ -9,11 +8,7 @@ use std::{
 use lazy_static::lazy_static;
 use structopt::{self, StructOpt};
 
-use super::read_lines;
-
-#[derive(Debug, thiserror::Error)]
-#[error("Failed to parse mode from '{0}'")]
-pub struct ParseModeError(String);
+use super::{mode::mode_enum, Input};
 
 #[derive(Debug, thiserror::Error)]
 #[error("Failed to parse cave system from '{0}'")]
@@ -25,47 +20,69 @@ pub struct InvalidCaveConnectionError(String);
 
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("paths"), long)]
     mode: Mode,
-}
 
-#[derive(Debug, StructOpt)]
-pub enum Mode {
-    Paths,
-    SmallCaveVisitTwiceOnce,
-}
+    #[structopt(long)]
+    tree: bool,
 
-impl FromStr for Mode {
-    type Err = ParseModeError;
+    /// Print the first path found instead of every path
+    #[structopt(long, conflicts_with("tree"))]
+    any: bool,
 
-    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
-        match s {
-            "paths" => Ok(Mode::Paths),
-            "small-cave-visit-twice-once" => Ok(Mode::SmallCaveVisitTwiceOnce),
-            _ => Err(ParseModeError(s.to_owned())),
-        }
+    /// Name of the cave the paths start from
+    #[structopt(long)]
+    start: Option<String>,
+
+    /// Name of the cave the paths end at
+    #[structopt(long)]
+    end: Option<String>,
+}
+
+mode_enum! {
+    pub enum Mode {
+        Paths => "paths",
+        SmallCaveVisitTwiceOnce => "small-cave-visit-twice-once",
     }
 }
 
 impl Command {
     pub fn run(&self) -> anyhow::Result<()> {
         let mut system = CaveSystem::parse(
-            read_lines(&self.input)?.iter().map(String::as_ref),
+            self.input.lines(SAMPLE)?.iter().map(String::as_ref),
         )?;
+        let start = self.start.as_deref().unwrap_or("start");
+        let end = self.end.as_deref().unwrap_or("end");
+        if self.start.is_some() || self.end.is_some() {
+            system = system.with_endpoints(start, end);
+        }
         if let Mode::SmallCaveVisitTwiceOnce = self.mode {
             system.set_allow_visit_one_small_cave_twice(true);
         }
 
-        println!("All cave paths from start to end");
-        let paths = system.paths().expect("valid input");
-        let mut paths: Vec<String> =
-            paths.into_iter().map(|path| path.join(",")).collect();
-        paths.sort_unstable_by_key(|path| path.to_lowercase());
-        for path in paths.iter() {
-            println!("  {}", path);
+        if self.any {
+            if let Some(path) = system.any_path() {
+                println!("{}", path.join(","));
+            } else {
+                println!("There's no path from {} to {}", start, end);
+            }
+            return Ok(());
+        }
+
+        let mut paths = system.paths_iter()?.collect::<Vec<_>>();
+        println!("All cave paths from {} to {}", start, end);
+        sort_paths(&mut paths);
+        if self.tree {
+            for line in render_path_tree(&paths).lines() {
+                println!("  {}", line);
+            }
+        } else {
+            for path in paths.iter() {
+                println!("  {}", path.join(","));
+            }
         }
         println!("  Total paths: {}", paths.len());
         println!(
@@ -76,148 +93,230 @@ impl Command {
     }
 }
 
+/// Orders the paths by their caves' names, ignoring case.
+fn sort_paths(paths: &mut [Vec<String>]) {
+    paths.sort_unstable_by_key(|path| path.join(",").to_lowercase());
+}
+
+/// Renders the paths, in order, as a tree sharing the common prefixes,
+/// indenting each cave two spaces deeper than the cave before it.
+fn render_path_tree(paths: &[Vec<String>]) -> String {
+    #[derive(Default)]
+    struct Node {
+        children: Vec<(String, Node)>,
+    }
+
+    fn render(node: &Node, depth: usize, tree: &mut String) {
+        for (name, child) in node.children.iter() {
+            tree.push_str(&format!(
+                "{:indent$}{}\n",
+                "",
+                name,
+                indent = depth * 2
+            ));
+            render(child, depth + 1, tree);
+        }
+    }
+
+    let mut root = Node::default();
+    for path in paths {
+        let mut node = &mut root;
+        for name in path {
+            let index = match node
+                .children
+                .iter()
+                .position(|(child_name, _)| child_name == name)
+            {
+                Some(index) => index,
+                None => {
+                    node.children.push((name.clone(), Node::default()));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index].1;
+        }
+    }
+
+    let mut tree = String::new();
+    render(&root, 0, &mut tree);
+    tree
+}
+
 lazy_static! {
     static ref EMPTY_ADJOINING_CAVE_VEC: Vec<usize> = Vec::new();
 }
 
-struct CaveSystem {
+/// Caves and the connections between them.
+pub struct CaveSystem {
     caves: Vec<Cave>,
+    cave_indices: HashMap<String, usize>,
     connections: HashMap<usize, Vec<usize>>,
     allow_visit_one_small_twice: bool,
 }
 
+impl Default for CaveSystem {
+    fn default() -> Self {
+        CaveSystem::new()
+    }
+}
+
 impl CaveSystem {
-    fn parse<'a, Iter: Iterator<Item = &'a str>>(
+    pub fn new() -> Self {
+        CaveSystem {
+            caves: vec![],
+            cave_indices: HashMap::new(),
+            connections: HashMap::new(),
+            allow_visit_one_small_twice: false,
+        }
+    }
+
+    pub fn parse<'a, Iter: Iterator<Item = &'a str>>(
         lines: Iter,
     ) -> result::Result<Self, ParseCaveSystemError> {
-        let mut caves: Vec<Cave> = vec![];
-        let mut cave_indices: HashMap<&str, usize> = HashMap::new();
-        let mut cave_connections: HashMap<usize, Vec<usize>> = HashMap::new();
-
-        let mut store_cave =
-            |cave_name: &'a str| -> Result<usize, ParseCaveSystemError> {
-                if let Some(index) = cave_indices.get(cave_name) {
-                    Ok(*index)
-                } else {
-                    let index = caves.len();
-                    caves.push(Cave::from_str(cave_name)?);
-                    cave_indices.insert(cave_name, index);
-                    Ok(index)
-                }
-            };
-
+        let mut system = CaveSystem::new();
         for line in lines {
             let line = line.trim();
-            if line.is_empty() {
+            if line.is_empty() || line.starts_with('#') {
                 continue;
             }
 
             let mut connection = line.split('-');
-            let (start_index, end_index) =
-                match (connection.next(), connection.next(), connection.next())
-                {
-                    (Some(start), Some(end), None) => {
-                        (store_cave(start)?, store_cave(end)?)
-                    }
-                    _ => return Err(ParseCaveSystemError(line.to_owned())),
-                };
-            cave_connections
-                .entry(start_index)
-                .or_insert_with(Vec::new)
-                .push(end_index);
-            cave_connections
-                .entry(end_index)
-                .or_insert_with(Vec::new)
-                .push(start_index);
+            match (connection.next(), connection.next(), connection.next()) {
+                (Some(start), Some(end), None) => {
+                    system.add_connection(start, end)?
+                }
+                _ => return Err(ParseCaveSystemError(line.to_owned())),
+            };
         }
 
-        Ok(CaveSystem {
-            caves,
-            connections: cave_connections,
-            allow_visit_one_small_twice: false,
-        })
+        Ok(system)
     }
 
-    fn paths(
-        &self,
-    ) -> result::Result<Vec<Vec<&'_ str>>, InvalidCaveConnectionError> {
-        if let Some(start_index) = self
-            .caves
-            .iter()
-            .position(|cave| matches!(cave, Cave::Start))
-        {
-            Ok(self
-                .find_paths_to_end(start_index, &HashSet::new(), false)
-                .into_iter()
-                .map(|mut path| {
-                    path.reverse();
-                    path
-                })
-                .collect())
+    /// Connects the caves named `a` and `b`, adding either cave to the
+    /// system when it isn't already known.
+    pub fn add_connection(
+        &mut self,
+        a: &str,
+        b: &str,
+    ) -> result::Result<(), ParseCaveSystemError> {
+        let a_index = self.store_cave(a)?;
+        let b_index = self.store_cave(b)?;
+        self.connections.entry(a_index).or_default().push(b_index);
+        self.connections.entry(b_index).or_default().push(a_index);
+        Ok(())
+    }
+
+    fn store_cave(
+        &mut self,
+        cave_name: &str,
+    ) -> result::Result<usize, ParseCaveSystemError> {
+        if let Some(index) = self.cave_indices.get(cave_name) {
+            Ok(*index)
         } else {
-            Err(InvalidCaveConnectionError("missing 'start'".to_owned()))
+            let index = self.caves.len();
+            self.caves.push(Cave::from_str(cave_name)?);
+            self.cave_indices.insert(cave_name.to_owned(), index);
+            Ok(index)
         }
     }
 
-    fn find_paths_to_end<'a>(
-        &'a self,
-        cave_index: usize,
-        visited_small_caves: &HashSet<usize>,
-        mut visited_one_small_cave_twice: bool,
-    ) -> Vec<Vec<&'a str>> {
-        let cave = self.get_cave(cave_index);
-        let adjoining_cave_indices = self
-            .get_adjoining_cave_indices(cave_index)
+    pub fn paths(
+        &self,
+    ) -> result::Result<Vec<Vec<&'_ str>>, InvalidCaveConnectionError> {
+        Ok(self
+            .path_indices_iter()?
+            .map(|path| {
+                path.into_iter()
+                    .map(|cave_index| self.get_cave(cave_index).name())
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Lazily yields every start to end path using an explicit depth first
+    /// search stack of partial paths rather than collecting them all.
+    pub fn paths_iter(
+        &self,
+    ) -> result::Result<
+        impl Iterator<Item = Vec<String>> + '_,
+        InvalidCaveConnectionError,
+    > {
+        Ok(self.path_indices_iter()?.map(|path| {
+            path.into_iter()
+                .map(|cave_index| self.get_cave(cave_index).name().to_owned())
+                .collect()
+        }))
+    }
+
+    /// Lazily yields the cave indices of every start to end path.
+    fn path_indices_iter(
+        &self,
+    ) -> result::Result<
+        impl Iterator<Item = Vec<usize>> + '_,
+        InvalidCaveConnectionError,
+    > {
+        let start_index = self
+            .caves
             .iter()
-            .copied()
-            .filter(|adjoining_cave_index| {
-                if visited_small_caves.contains(adjoining_cave_index) {
-                    if self.allow_visit_one_small_twice
-                        && !visited_one_small_cave_twice
-                        && self.get_cave(*adjoining_cave_index).is_small()
+            .position(|cave| matches!(cave, Cave::Start(_)))
+            .ok_or_else(|| {
+                InvalidCaveConnectionError("missing 'start'".to_owned())
+            })?;
+        let mut stack = vec![(vec![start_index], false)];
+        Ok(std::iter::from_fn(move || {
+            while let Some((path, visited_one_small_cave_twice)) = stack.pop() {
+                let cave_index = *path.last().expect("path starts with a cave");
+                if self.get_cave(cave_index).is_end() {
+                    return Some(path);
+                }
+
+                for adjoining_cave_index in
+                    self.get_adjoining_cave_indices(cave_index).iter().copied()
+                {
+                    let adjoining_cave = self.get_cave(adjoining_cave_index);
+                    let visited_twice = if !adjoining_cave.is_big()
+                        && path.contains(&adjoining_cave_index)
                     {
-                        visited_one_small_cave_twice = true;
-                        true
+                        if self.allow_visit_one_small_twice
+                            && !visited_one_small_cave_twice
+                            && adjoining_cave.is_small()
+                        {
+                            true
+                        } else {
+                            continue;
+                        }
                     } else {
-                        false
-                    }
-                } else {
-                    true
+                        visited_one_small_cave_twice
+                    };
+                    let mut next_path = path.clone();
+                    next_path.push(adjoining_cave_index);
+                    stack.push((next_path, visited_twice));
                 }
-            })
-            .collect::<Vec<_>>();
-        let adjoining_caves_paths =
-            adjoining_cave_indices.iter().map(|adjoining_cave_index| {
-                let adjoining_cave_index = *adjoining_cave_index;
-                let adjoining_cave = self.get_cave(adjoining_cave_index);
-                if adjoining_cave.is_end() {
-                    vec![vec!["end"]]
-                } else if cave.is_big() {
-                    self.find_paths_to_end(
-                        adjoining_cave_index,
-                        visited_small_caves,
-                        visited_one_small_cave_twice,
-                    )
-                } else {
-                    let visited_small_caves = HashSet::from([cave_index])
-                        .union(visited_small_caves)
-                        .copied()
-                        .collect();
-                    self.find_paths_to_end(
-                        adjoining_cave_index,
-                        &visited_small_caves,
-                        visited_one_small_cave_twice,
-                    )
-                }
-            });
-        adjoining_caves_paths
-            .into_iter()
-            .flat_map(|cave_paths| cave_paths.into_iter())
-            .map(|mut path| {
-                path.push(self.get_cave(cave_index).name());
-                path
-            })
-            .collect()
+            }
+            None
+        }))
+    }
+
+    /// Renders every start to end path, sorted like the command lists them,
+    /// as a tree with `start` at its root and a branch wherever the paths
+    /// part ways. The tree is empty when there's no start.
+    pub fn path_tree(&self) -> String {
+        let mut paths = match self.paths_iter() {
+            Ok(paths) => paths.collect::<Vec<_>>(),
+            Err(_) => return String::new(),
+        };
+        sort_paths(&mut paths);
+        render_path_tree(&paths)
+    }
+
+    /// Returns the first start to end path found by a depth first search
+    /// without enumerating every path.
+    pub fn any_path(&self) -> Option<Vec<&'_ str>> {
+        self.path_indices_iter().ok()?.next().map(|path| {
+            path.into_iter()
+                .map(|cave_index| self.get_cave(cave_index).name())
+                .collect()
+        })
     }
 
     fn get_cave(&self, cave_index: usize) -> &Cave {
@@ -230,19 +329,39 @@ impl CaveSystem {
         if let Some(indices) = self.connections.get(&cave_index) {
             indices
         } else {
-            &*EMPTY_ADJOINING_CAVE_VEC
+            &EMPTY_ADJOINING_CAVE_VEC
         }
     }
 
-    fn set_allow_visit_one_small_cave_twice(&mut self, allow: bool) {
+    pub fn set_allow_visit_one_small_cave_twice(&mut self, allow: bool) {
         self.allow_visit_one_small_twice = allow;
     }
+
+    /// Designates the caves named `start` and `end` as the path endpoints,
+    /// reclassifying the previous endpoints as ordinary caves.
+    pub fn with_endpoints(mut self, start: &str, end: &str) -> Self {
+        self.caves = self
+            .caves
+            .into_iter()
+            .map(|cave| {
+                let name = cave.name().to_owned();
+                if name == start {
+                    Cave::Start(name)
+                } else if name == end {
+                    Cave::End(name)
+                } else {
+                    Cave::sized(name).expect("parsed cave names are sized")
+                }
+            })
+            .collect();
+        self
+    }
 }
 
 #[derive(Debug)]
 enum Cave {
-    Start,
-    End,
+    Start(String),
+    End(String),
     Big(String),
     Small(String),
 }
@@ -250,15 +369,26 @@ enum Cave {
 impl Cave {
     fn name(&self) -> &str {
         match self {
-            Cave::Start => "start",
-            Cave::End => "end",
+            Cave::Start(name) => name,
+            Cave::End(name) => name,
             Cave::Big(name) => name,
             Cave::Small(name) => name,
         }
     }
 
+    /// Classifies a cave that isn't an endpoint by the case of its name.
+    fn sized(name: String) -> Result<Cave, ParseCaveSystemError> {
+        if name.chars().all(|character| character.is_uppercase()) {
+            Ok(Cave::Big(name))
+        } else if name.chars().all(|character| character.is_lowercase()) {
+            Ok(Cave::Small(name))
+        } else {
+            Err(ParseCaveSystemError(name))
+        }
+    }
+
     fn is_end(&self) -> bool {
-        matches!(self, Cave::End)
+        matches!(self, Cave::End(_))
     }
 
     fn is_big(&self) -> bool {
@@ -281,23 +411,23 @@ impl FromStr for Cave {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "start" => Ok(Cave::Start),
-            "end" => Ok(Cave::End),
-            name => {
-                let name = name.to_owned();
-                if name.chars().all(|character| character.is_uppercase()) {
-                    Ok(Cave::Big(name))
-                } else if name.chars().all(|character| character.is_lowercase())
-                {
-                    Ok(Cave::Small(name))
-                } else {
-                    Err(ParseCaveSystemError(name))
-                }
-            }
+            "start" => Ok(Cave::Start(s.to_owned())),
+            "end" => Ok(Cave::End(s.to_owned())),
+            name => Cave::sized(name.to_owned()),
         }
     }
 }
 
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
 #[cfg(test)]
 mod tests {
     use super::CaveSystem;
@@ -339,6 +469,44 @@ mod tests {
         assert_equivalent_paths(&paths, SIMPLE_TEST.sorted_expected_paths);
     }
 
+    #[test]
+    fn cave_system_parse_skips_comments() {
+        let system = CaveSystem::parse(
+            [
+                "# the simple sample",
+                "start-A",
+                "start-b",
+                "",
+                "# caves off of A",
+                "A-c",
+                "A-b",
+                "b-d",
+                "   # indented comment",
+                "A-end",
+                "b-end",
+            ]
+            .into_iter(),
+        )
+        .expect("valid input");
+
+        let paths = system.paths().expect("valid");
+        assert_eq!(paths.len(), 10);
+        assert!(CaveSystem::parse(["start-A", "A end"].into_iter()).is_err());
+    }
+
+    #[test]
+    fn cave_system_add_connection() {
+        let mut system = CaveSystem::new();
+        for connection in SIMPLE_TEST.cave_connections {
+            let (a, b) = connection.split_once('-').expect("valid connection");
+            system.add_connection(a, b).expect("valid caves");
+        }
+
+        let paths = system.paths().expect("valid");
+        assert_eq!(paths.len(), 10);
+        assert_equivalent_paths(&paths, SIMPLE_TEST.sorted_expected_paths);
+    }
+
     #[test]
     fn cave_system_paths_larger() {
         let system =
@@ -351,18 +519,36 @@ mod tests {
 
     #[test]
     fn cave_system_paths_largest() {
-        let cave_connections = &[
-            "fs-end", "he-DX", "fs-he", "start-DX", "pj-DX", "end-zg", "zg-sl",
-            "zg-pj", "pj-he", "RW-he", "fs-DX", "pj-RW", "zg-RW", "start-pj",
-            "he-WI", "zg-he", "pj-fs", "start-RW",
-        ];
-        let system = CaveSystem::parse(cave_connections.iter().copied())
-            .expect("valid input");
+        let system =
+            CaveSystem::parse(LARGEST_CAVE_CONNECTIONS.iter().copied())
+                .expect("valid input");
 
         let paths = system.paths().expect("valid");
         assert_eq!(paths.len(), 226);
     }
 
+    #[test]
+    fn cave_system_paths_larger_visit_one_small_twice() {
+        let mut system =
+            CaveSystem::parse(LARGER_TEST.cave_connections.iter().copied())
+                .expect("valid input");
+        system.set_allow_visit_one_small_cave_twice(true);
+
+        let paths = system.paths().expect("valid");
+        assert_eq!(paths.len(), 103);
+    }
+
+    #[test]
+    fn cave_system_paths_largest_visit_one_small_twice() {
+        let mut system =
+            CaveSystem::parse(LARGEST_CAVE_CONNECTIONS.iter().copied())
+                .expect("valid input");
+        system.set_allow_visit_one_small_cave_twice(true);
+
+        let paths = system.paths().expect("valid");
+        assert_eq!(paths.len(), 3509);
+    }
+
     #[test]
     fn cave_system_paths_visit_one_small_twice() {
         let mut system = CaveSystem::parse(
@@ -381,6 +567,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cave_system_paths_iter() {
+        let mut system =
+            CaveSystem::parse(SIMPLE_TEST.cave_connections.iter().copied())
+                .expect("valid input");
+
+        let paths = system
+            .paths_iter()
+            .expect("has a start")
+            .collect::<Vec<_>>();
+        assert_eq!(paths.len(), 10);
+        assert_equivalent_paths(
+            &paths
+                .iter()
+                .map(|path| path.iter().map(String::as_ref).collect())
+                .collect::<Vec<_>>(),
+            SIMPLE_TEST.sorted_expected_paths,
+        );
+
+        system.set_allow_visit_one_small_cave_twice(true);
+        assert_eq!(
+            system.paths_iter().expect("has a start").count(),
+            SIMPLE_TEST_VISIT_ONE_SMALL_TWICE
+                .sorted_expected_paths
+                .len()
+        );
+    }
+
+    #[test]
+    fn cave_system_path_tree() {
+        let system =
+            CaveSystem::parse(SIMPLE_TEST.cave_connections.iter().copied())
+                .expect("valid input");
+
+        let tree = system.path_tree();
+        let roots = tree
+            .lines()
+            .filter(|line| !line.starts_with(' '))
+            .collect::<Vec<_>>();
+        let children = tree
+            .lines()
+            .filter(|line| line.starts_with("  ") && !line.starts_with("   "))
+            .collect::<Vec<_>>();
+
+        assert_eq!(roots, vec!["start"]);
+        assert_eq!(children, vec!["  A", "  b"]);
+        assert_eq!(
+            tree.lines().filter(|line| line.ends_with("end")).count(),
+            10
+        );
+    }
+
+    #[test]
+    fn cave_system_any_path() {
+        let system =
+            CaveSystem::parse(SIMPLE_TEST.cave_connections.iter().copied())
+                .expect("valid input");
+
+        let path = system.any_path().expect("path exists");
+
+        assert_eq!(path.first(), Some(&"start"));
+        assert_eq!(path.last(), Some(&"end"));
+        assert!(SIMPLE_TEST
+            .sorted_expected_paths
+            .contains(&path.join(",").as_ref()));
+    }
+
+    #[test]
+    fn cave_system_paths_with_endpoints() {
+        let system = CaveSystem::parse(
+            [
+                "begin-A", "begin-b", "A-c", "A-b", "b-d", "A-finish",
+                "b-finish",
+            ]
+            .into_iter(),
+        )
+        .expect("valid input")
+        .with_endpoints("begin", "finish");
+
+        let paths = system.paths().expect("valid");
+        let expected_paths = SIMPLE_TEST
+            .sorted_expected_paths
+            .iter()
+            .map(|path| path.replace("start", "begin").replace("end", "finish"))
+            .collect::<Vec<_>>();
+        assert_equivalent_paths(
+            &paths,
+            &expected_paths
+                .iter()
+                .map(String::as_ref)
+                .collect::<Vec<_>>(),
+        );
+    }
+
     fn assert_equivalent_paths(
         paths: &[Vec<&str>],
         sorted_expected_paths: &[&str],
@@ -412,6 +692,12 @@ mod tests {
         }
     }
 
+    const LARGEST_CAVE_CONNECTIONS: &[&str] = &[
+        "fs-end", "he-DX", "fs-he", "start-DX", "pj-DX", "end-zg", "zg-sl",
+        "zg-pj", "pj-he", "RW-he", "fs-DX", "pj-RW", "zg-RW", "start-pj",
+        "he-WI", "zg-he", "pj-fs", "start-RW",
+    ];
+
     lazy_static! {
         static ref SIMPLE_TEST: TestCase = TestCase::new(
             &["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end",],
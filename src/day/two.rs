@@ -1,149 +1,386 @@
-use anyhow::{anyhow, Context, Result};
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    fmt::{self, Display, Formatter},
+    io::BufRead,
+    str::FromStr,
 };
+
+use anyhow::anyhow;
 use structopt::{self, StructOpt};
 
+use super::{mode::mode_enum, Input};
+
+mode_enum! {
+    pub enum Mode {
+        Movement => "movement",
+        Aim => "aim",
+        Current => "current",
+    }
+}
+
 #[derive(Debug, StructOpt)]
 pub struct Command {
-    #[structopt(required(true), parse(from_os_str))]
-    input: PathBuf,
+    #[structopt(flatten)]
+    input: Input,
 
     #[structopt(default_value("movement"), long)]
-    mode: String,
+    mode: Mode,
+
+    #[structopt(default_value("0"), long, allow_hyphen_values(true))]
+    current: i32,
+
+    /// Fail on the first malformed line instead of skipping it
+    #[structopt(long)]
+    strict: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to parse movement from '{0}'")]
+pub struct ParseMoveError(String);
+
+/// A single movement of the submarine by some distance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Forward(i32),
+    Up(i32),
+    Down(i32),
+}
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_error = || ParseMoveError(s.to_owned());
+        let (direction, distance) =
+            s.split_once(' ').ok_or_else(parse_error)?;
+        let distance = distance.parse().map_err(|_| parse_error())?;
+        match direction {
+            FORWARD => Ok(Move::Forward(distance)),
+            UP => Ok(Move::Up(distance)),
+            DOWN => Ok(Move::Down(distance)),
+            _ => Err(parse_error()),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Forward(distance) => write!(f, "{} {}", FORWARD, distance),
+            Move::Up(distance) => write!(f, "{} {}", UP, distance),
+            Move::Down(distance) => write!(f, "{} {}", DOWN, distance),
+        }
+    }
+}
+
+/// The final position along with how many malformed lines were skipped
+/// getting there.
+#[derive(Debug, PartialEq, Eq)]
+struct Course {
+    horizontal: i32,
+    vertical: i32,
+    skipped: usize,
+}
+
+impl Display for Course {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "final position: horizontal={} depth={} product={}",
+            self.horizontal,
+            self.vertical,
+            self.horizontal * self.vertical
+        )
+    }
 }
 
 const FORWARD: &str = "forward";
 const UP: &str = "up";
 const DOWN: &str = "down";
-const MODE_MOVEMENT: &str = "movement";
-const MODE_AIM: &str = "aim";
 
 impl Command {
-    pub fn run(&self) -> Result<()> {
-        let file = File::open(&self.input).with_context(|| {
-            format!("failed to open file '{}'", self.input.display())
-        })?;
-        match self.mode.as_ref() {
-            MODE_MOVEMENT => {
-                self.report_position_by_movements(BufReader::new(file));
-                Ok(())
+    /// Prints every movement along the way when `verbosity` is above zero.
+    pub fn run(&self, verbosity: u8) -> anyhow::Result<()> {
+        let reader = self.input.reader(SAMPLE)?;
+        let verbose = verbosity > 0;
+        let course = match self.mode {
+            Mode::Movement => {
+                self.report_position_by_movements(reader, verbose)?
             }
-            MODE_AIM => {
-                self.report_position_by_aim(BufReader::new(file));
-                Ok(())
+            Mode::Aim => self.report_position_by_aim(reader, 0, verbose)?,
+            Mode::Current => {
+                self.report_position_by_aim(reader, self.current, verbose)?
             }
-            invalid_mode => Err(anyhow!("invalid mode '{}'", invalid_mode)),
+        };
+        println!("{}", course);
+        if course.skipped > 0 {
+            eprintln!("skipped {} malformed lines", course.skipped);
         }
+        Ok(())
     }
 
-    fn report_position_by_movements(&self, reader: BufReader<File>) {
-        let mut horizontal = 0;
-        let mut vertical = 0;
-        for line in reader.lines() {
-            match line {
-                Ok(ref text) => {
-                    let movement: Vec<&str> = text.split(' ').collect();
-                    if movement.len() != 2 {
-                        eprintln!("invalid movement entry '{}'", &text);
-                        continue;
-                    }
-                    let distance: i32 = match movement[1].parse() {
-                        Ok(value) => value,
-                        Err(err) => {
-                            eprintln!(
-                                "failed to parse movement '{}' due to {:?}",
-                                &text, &err
-                            );
-                            continue;
-                        }
-                    };
-                    let direction = match movement[0] {
-                        FORWARD => {
-                            horizontal += distance;
-                            FORWARD
-                        }
-                        UP => {
-                            vertical -= distance;
-                            UP
-                        }
-                        DOWN => {
-                            vertical += distance;
-                            DOWN
-                        }
-                        _ => {
-                            eprintln!("failed to parse movement '{}'", &text);
-                            continue;
-                        }
-                    };
-                    println!(
-                        "{} {} ({}:{})[{}]",
-                        direction,
-                        distance,
-                        horizontal,
-                        vertical,
-                        horizontal * vertical
-                    );
-                }
-                Err(err) => eprintln!("failed to read text: {:?}", err),
+    fn report_position_by_movements<R: BufRead>(
+        &self,
+        reader: R,
+        verbose: bool,
+    ) -> anyhow::Result<Course> {
+        report_position(reader, false, 0, verbose, self.strict)
+    }
+
+    /// The `current` drifts the submarine further along on every forward
+    /// movement.
+    fn report_position_by_aim<R: BufRead>(
+        &self,
+        reader: R,
+        current: i32,
+        verbose: bool,
+    ) -> anyhow::Result<Course> {
+        report_position(reader, true, current, verbose, self.strict)
+    }
+}
+
+/// Parses the movements, skipping and reporting any malformed lines unless
+/// `strict` is set, when the first one fails with its line number.
+fn parse_moves<R: BufRead>(
+    reader: R,
+    strict: bool,
+) -> anyhow::Result<(Vec<Move>, usize)> {
+    let mut moves = vec![];
+    let mut skipped = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let err = match line.map(|text| text.parse::<Move>()) {
+            Ok(Ok(movement)) => {
+                moves.push(movement);
+                continue;
             }
+            Ok(Err(err)) => err.to_string(),
+            Err(err) => format!("failed to read text: {:?}", err),
+        };
+        if strict {
+            return Err(anyhow!("line {}: {}", index + 1, err));
         }
+        eprintln!("{}", err);
+        skipped += 1;
     }
+    Ok((moves, skipped))
+}
 
-    fn report_position_by_aim(&self, reader: BufReader<File>) {
-        let mut horizontal = 0;
-        let mut vertical = 0;
-        let mut aim = 0;
-        for line in reader.lines() {
-            match line {
-                Ok(ref text) => {
-                    let movement: Vec<&str> = text.split(' ').collect();
-                    if movement.len() != 2 {
-                        eprintln!("invalid movement entry '{}'", &text);
-                        continue;
-                    }
-                    let distance: i32 = match movement[1].parse() {
-                        Ok(value) => value,
-                        Err(err) => {
-                            eprintln!(
-                                "failed to parse movement '{}' due to {:?}",
-                                &text, &err
-                            );
-                            continue;
-                        }
-                    };
-                    let direction = match movement[0] {
-                        FORWARD => {
-                            horizontal += distance;
-                            vertical += aim * distance;
-                            FORWARD
-                        }
-                        UP => {
-                            aim -= distance;
-                            UP
-                        }
-                        DOWN => {
-                            aim += distance;
-                            DOWN
-                        }
-                        _ => {
-                            eprintln!("failed to parse movement '{}'", &text);
-                            continue;
-                        }
-                    };
-                    println!(
-                        "{} {} ({}:{})[{}]",
-                        direction,
-                        distance,
-                        horizontal,
-                        vertical,
-                        horizontal * vertical
-                    );
+fn report_position<R: BufRead>(
+    reader: R,
+    aim: bool,
+    current: i32,
+    verbose: bool,
+    strict: bool,
+) -> anyhow::Result<Course> {
+    let (moves, skipped) = parse_moves(reader, strict)?;
+    let mut position = (0, 0);
+    for (movement, next) in moves.iter().zip(positions(&moves, aim, current)) {
+        position = next;
+        if verbose {
+            let (horizontal, vertical) = position;
+            println!(
+                "{} ({}:{})[{}]",
+                movement,
+                horizontal,
+                vertical,
+                horizontal * vertical
+            );
+        }
+    }
+    Ok(Course {
+        horizontal: position.0,
+        vertical: position.1,
+        skipped,
+    })
+}
+
+/// The horizontal position and depth after each of the `moves`. With `aim`
+/// up and down turn the submarine while forward moves it along and dives by
+/// the aim, otherwise up and down change the depth directly. The `current`
+/// carries the submarine further on each forward movement.
+fn positions(
+    moves: &[Move],
+    aim: bool,
+    current: i32,
+) -> impl Iterator<Item = (i32, i32)> + '_ {
+    moves.iter().scan(
+        (0, 0, 0),
+        move |(horizontal, depth, heading), movement| {
+            match (*movement, aim) {
+                (Move::Forward(distance), _) => {
+                    *horizontal += distance + current;
+                    *depth += *heading * distance;
                 }
-                Err(err) => eprintln!("failed to read text: {:?}", err),
+                (Move::Up(distance), true) => *heading -= distance,
+                (Move::Down(distance), true) => *heading += distance,
+                (Move::Up(distance), false) => *depth -= distance,
+                (Move::Down(distance), false) => *depth += distance,
             }
+            Some((*horizontal, *depth))
+        },
+    )
+}
+
+/// The horizontal position and depth reached after all of the `moves`.
+pub fn final_position(moves: &[Move], aim: bool) -> (i32, i32) {
+    positions(moves, aim, 0).last().unwrap_or((0, 0))
+}
+
+/// The example input from the puzzle description.
+const SAMPLE: &str = r"forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use std::io::BufRead;
+
+    use super::{final_position, Command, Course, Input, Mode, Move, SAMPLE};
+
+    fn command() -> Command {
+        Command {
+            input: Input::default(),
+            mode: Mode::Movement,
+            current: 0,
+            strict: false,
         }
     }
+
+    #[test]
+    fn mode_is_parsed_with_the_arguments() {
+        let command = Command::from_iter_safe(["two", "--sample"])
+            .expect("valid arguments");
+        assert_eq!(command.mode, Mode::Movement);
+
+        let command =
+            Command::from_iter_safe(["two", "--sample", "--mode", "aim"])
+                .expect("valid arguments");
+        assert_eq!(command.mode, Mode::Aim);
+
+        assert!(
+            Command::from_iter_safe(["two", "--sample", "--mode", "sail"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn move_from_str() {
+        assert_eq!(
+            "forward 5".parse::<Move>().expect("valid"),
+            Move::Forward(5)
+        );
+        assert_eq!("up 3".parse::<Move>().expect("valid"), Move::Up(3));
+        assert_eq!("down 8".parse::<Move>().expect("valid"), Move::Down(8));
+        assert!("sideways 3".parse::<Move>().is_err());
+        assert!("up".parse::<Move>().is_err());
+        assert!("up three".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn final_position_test() {
+        let moves = SAMPLE
+            .as_bytes()
+            .lines()
+            .map(|line| line.expect("readable").parse())
+            .collect::<Result<Vec<Move>, _>>()
+            .expect("valid moves");
+
+        let (horizontal, depth) = final_position(&moves, false);
+        assert_eq!((horizontal, depth, horizontal * depth), (15, 10, 150));
+        let (horizontal, depth) = final_position(&moves, true);
+        assert_eq!((horizontal, depth, horizontal * depth), (15, 60, 900));
+        assert_eq!(final_position(&[], true), (0, 0));
+    }
+
+    #[test]
+    fn report_position_counts_skipped_lines() {
+        let command = command();
+
+        assert_eq!(
+            command
+                .report_position_by_movements(INPUT.as_bytes(), false)
+                .expect("lenient")
+                .skipped,
+            2
+        );
+        assert_eq!(
+            command
+                .report_position_by_aim(INPUT.as_bytes(), 0, false)
+                .expect("lenient")
+                .skipped,
+            2
+        );
+        assert_eq!(
+            command
+                .report_position_by_aim(&b"up 3\n"[..], 0, false)
+                .expect("lenient")
+                .skipped,
+            0
+        );
+    }
+
+    #[test]
+    fn strict_fails_on_the_first_malformed_line() {
+        let command = Command {
+            strict: true,
+            ..command()
+        };
+
+        assert_eq!(
+            command
+                .report_position_by_movements(INPUT.as_bytes(), false)
+                .unwrap_err()
+                .to_string(),
+            "line 3: Failed to parse movement from 'sideways 3'"
+        );
+        assert!(command
+            .report_position_by_aim(SAMPLE.as_bytes(), 0, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn report_position_by_aim_with_current() {
+        let command = command();
+
+        let still = command
+            .report_position_by_aim(SAMPLE.as_bytes(), 0, false)
+            .expect("valid input");
+        let drifting = command
+            .report_position_by_aim(SAMPLE.as_bytes(), 2, false)
+            .expect("valid input");
+
+        assert_eq!(
+            still,
+            Course {
+                horizontal: 15,
+                vertical: 60,
+                skipped: 0
+            }
+        );
+        assert_eq!(
+            still.to_string(),
+            "final position: horizontal=15 depth=60 product=900"
+        );
+        // three forward movements each drift two further along
+        assert_eq!(
+            drifting,
+            Course {
+                horizontal: 21,
+                vertical: 60,
+                skipped: 0
+            }
+        );
+    }
+
+    const INPUT: &str = r"forward 5
+down 5
+sideways 3
+forward 8
+up
+down 8
+forward 2";
 }
@@ -0,0 +1,4 @@
+//! The Advent of Code 2021 solvers, one module per day, usable without the
+//! `aoc` command line.
+
+pub mod day;
@@ -1,9 +1,23 @@
-use structopt::{self, StructOpt};
+use std::{
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant},
+};
 
-mod day;
+use aoc::day;
+use structopt::{self, StructOpt};
 
 #[derive(Debug, StructOpt)]
 struct AdventOfCode {
+    /// Print how long the command took to stderr
+    #[structopt(long)]
+    timing: bool,
+    /// How answers are printed: text or json
+    #[structopt(default_value("text"), long)]
+    format: day::Format,
+    /// Print more of each day's working, repeat for more detail
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
     #[structopt(subcommand)]
     command: Command,
 }
@@ -27,29 +41,309 @@ enum Command {
     Fifteen(day::fifteen::Command),
     Sixteen(day::sixteen::Command),
     Seventeen(day::seventeen::Command),
+    Bench(BenchCommand),
+    All(AllCommand),
+}
+
+/// The subcommand of each day in order, day one first.
+const DAYS: [&str; 17] = [
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+];
+
+/// Runs every day against the `dayNN.txt` inputs found in a directory.
+#[derive(Debug, StructOpt)]
+struct BenchCommand {
+    #[structopt(required(true), parse(from_os_str))]
+    directory: PathBuf,
+}
+
+impl BenchCommand {
+    fn run(&self, format: day::Format, verbosity: u8) -> anyhow::Result<()> {
+        let mut timings: Vec<(&str, Option<Duration>)> = vec![];
+        for (index, name) in DAYS.iter().enumerate() {
+            let input = day_input(&self.directory, index);
+            if !input.is_file() {
+                continue;
+            }
+            let command = Command::from_iter_safe(bench_args(name, &input))?;
+            let start = Instant::now();
+            match command.run(format, verbosity) {
+                Ok(()) => timings.push((name, Some(start.elapsed()))),
+                Err(err) => {
+                    eprintln!("day {} failed: {}", name, err);
+                    timings.push((name, None));
+                }
+            }
+        }
+
+        println!("{:<10} {:>12}", "day", "elapsed");
+        for (name, elapsed) in &timings {
+            match elapsed {
+                Some(elapsed) => println!(
+                    "{:<10} {:>10.1}ms",
+                    name,
+                    elapsed.as_secs_f64() * 1000.0
+                ),
+                None => println!("{:<10} {:>12}", name, "failed"),
+            }
+        }
+        match timings
+            .iter()
+            .filter(|(_, elapsed)| elapsed.is_none())
+            .count()
+        {
+            0 => Ok(()),
+            failed => Err(anyhow::anyhow!("{} days failed", failed)),
+        }
+    }
+}
+
+/// Runs every day against the `dayNN.txt` inputs found in a directory,
+/// printing each day's output under a header.
+#[derive(Debug, StructOpt)]
+struct AllCommand {
+    #[structopt(required(true), parse(from_os_str))]
+    directory: PathBuf,
+}
+
+impl AllCommand {
+    /// Keeps going after a day fails, failing once every day has run.
+    fn run(&self, format: day::Format, verbosity: u8) -> anyhow::Result<()> {
+        let mut failed = 0;
+        for (index, name) in DAYS.iter().enumerate() {
+            let input = day_input(&self.directory, index);
+            if !input.is_file() {
+                println!("day{:02}: no input", index + 1);
+                continue;
+            }
+            println!("== day{:02}: {} ==", index + 1, name);
+            let command = Command::from_iter_safe(bench_args(name, &input))?;
+            if let Err(err) = command.run(format, verbosity) {
+                eprintln!("day {} failed: {}", name, err);
+                failed += 1;
+            }
+        }
+        match failed {
+            0 => Ok(()),
+            failed => Err(anyhow::anyhow!("{} days failed", failed)),
+        }
+    }
+}
+
+/// The input for the day at `index`, where day one is at index zero.
+fn day_input(directory: &Path, index: usize) -> PathBuf {
+    directory.join(format!("day{:02}.txt", index + 1))
+}
+
+/// The arguments that run day `name` against `input` with its defaults.
+/// Day one takes its file as an option while days three and fourteen need a
+/// system and a step count.
+fn bench_args(name: &str, input: &Path) -> Vec<String> {
+    let mut args = vec!["aoc".to_owned(), name.to_owned()];
+    match name {
+        "one" => {
+            args.extend(["--input".to_owned(), input.display().to_string()])
+        }
+        "three" => args.extend([
+            input.display().to_string(),
+            "--system".to_owned(),
+            "power-consumption".to_owned(),
+        ]),
+        "fourteen" => args.extend([
+            input.display().to_string(),
+            "--steps".to_owned(),
+            "10".to_owned(),
+        ]),
+        _ => args.push(input.display().to_string()),
+    }
+    args
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::One(_) => "one",
+            Command::Two(_) => "two",
+            Command::Three(_) => "three",
+            Command::Four(_) => "four",
+            Command::Five(_) => "five",
+            Command::Six(_) => "six",
+            Command::Seven(_) => "seven",
+            Command::Eight(_) => "eight",
+            Command::Nine(_) => "nine",
+            Command::Ten(_) => "ten",
+            Command::Eleven(_) => "eleven",
+            Command::Twelve(_) => "twelve",
+            Command::Thirteen(_) => "thirteen",
+            Command::Fourteen(_) => "fourteen",
+            Command::Fifteen(_) => "fifteen",
+            Command::Sixteen(_) => "sixteen",
+            Command::Seventeen(_) => "seventeen",
+            Command::Bench(_) => "bench",
+            Command::All(_) => "all",
+        }
+    }
+
+    /// What `--timing` reports the time of: the day, or the whole of a
+    /// command running several days.
+    fn timing_label(&self) -> String {
+        match self {
+            Command::Bench(_) | Command::All(_) => self.name().to_owned(),
+            _ => format!("day {}", self.name()),
+        }
+    }
+
+    fn run(self, format: day::Format, verbosity: u8) -> anyhow::Result<()> {
+        match self {
+            Command::One(command) => command.run(),
+            Command::Two(command) => command.run(verbosity),
+            Command::Three(command) => command.run(),
+            Command::Four(command) => command.run(),
+            Command::Five(command) => command.run(),
+            Command::Six(command) => command.run(verbosity),
+            Command::Seven(command) => command.run(format),
+            Command::Eight(command) => command.run(),
+            Command::Nine(command) => command.run(format),
+            Command::Ten(command) => command.run(format),
+            Command::Eleven(command) => command.run(),
+            Command::Twelve(command) => command.run(),
+            Command::Thirteen(command) => command.run(),
+            Command::Fourteen(command) => command.run(),
+            Command::Fifteen(command) => command.run(format),
+            Command::Sixteen(command) => command.run(),
+            Command::Seventeen(command) => command.run(),
+            Command::Bench(command) => command.run(format, verbosity),
+            Command::All(command) => command.run(format, verbosity),
+        }
+    }
 }
 
 fn main() {
     let opt = AdventOfCode::from_args();
-    if let Err(err) = match opt.command {
-        Command::One(command) => command.run(),
-        Command::Two(command) => command.run(),
-        Command::Three(command) => command.run(),
-        Command::Four(command) => command.run(),
-        Command::Five(command) => command.run(),
-        Command::Six(command) => command.run(),
-        Command::Seven(command) => command.run(),
-        Command::Eight(command) => command.run(),
-        Command::Nine(command) => command.run(),
-        Command::Ten(command) => command.run(),
-        Command::Eleven(command) => command.run(),
-        Command::Twelve(command) => command.run(),
-        Command::Thirteen(command) => command.run(),
-        Command::Fourteen(command) => command.run(),
-        Command::Fifteen(command) => command.run(),
-        Command::Sixteen(command) => command.run(),
-        Command::Seventeen(command) => command.run(),
-    } {
+    let label = opt.command.timing_label();
+    let start = Instant::now();
+    let result = opt.command.run(opt.format, opt.verbose);
+    if opt.timing {
+        eprintln!(
+            "{} took {:.1}ms",
+            label,
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+    }
+    if let Err(err) = result {
         eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use structopt::StructOpt;
+
+    use std::path::Path;
+
+    use super::{bench_args, day_input, AdventOfCode, Command, DAYS};
+
+    #[test]
+    fn sixteen_subcommand_is_accepted() {
+        let opt = AdventOfCode::from_iter_safe(["aoc", "sixteen", "input.txt"])
+            .expect("valid arguments");
+
+        assert!(matches!(opt.command, Command::Sixteen(_)));
+    }
+
+    #[test]
+    fn sample_replaces_the_input() {
+        // days three and fourteen have other required arguments
+        for name in DAYS
+            .into_iter()
+            .filter(|name| !["three", "fourteen"].contains(name))
+        {
+            let opt = AdventOfCode::from_iter_safe(["aoc", name, "--sample"]);
+
+            assert!(opt.is_ok(), "day {} accepts --sample", name);
+        }
+        assert!(AdventOfCode::from_iter_safe(["aoc", "nine"]).is_err());
+        assert!(AdventOfCode::from_iter_safe([
+            "aoc", "nine", "real.txt", "--sample"
+        ])
+        .is_err());
+        assert!(AdventOfCode::from_iter_safe([
+            "aoc", "six", "real.txt", "--sample"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn timing_flag() {
+        let opt = AdventOfCode::from_iter_safe(["aoc", "twelve", "input.txt"])
+            .expect("valid arguments");
+        assert!(!opt.timing);
+
+        let opt = AdventOfCode::from_iter_safe([
+            "aoc",
+            "--timing",
+            "twelve",
+            "input.txt",
+        ])
+        .expect("valid arguments");
+        assert!(opt.timing);
+        assert_eq!(opt.command.timing_label(), "day twelve");
+
+        let opt = AdventOfCode::from_iter_safe([
+            "aoc", "--timing", "bench", "inputs",
+        ])
+        .expect("valid arguments");
+        assert_eq!(opt.command.timing_label(), "bench");
+    }
+
+    #[test]
+    fn verbose_flag_counts() {
+        let opt = AdventOfCode::from_iter_safe(["aoc", "six", "input.txt"])
+            .expect("valid arguments");
+        assert_eq!(opt.verbose, 0);
+
+        let opt =
+            AdventOfCode::from_iter_safe(["aoc", "-vv", "six", "input.txt"])
+                .expect("valid arguments");
+        assert_eq!(opt.verbose, 2);
+    }
+
+    #[test]
+    fn day_input_test() {
+        assert_eq!(
+            day_input(Path::new("inputs"), 0),
+            Path::new("inputs/day01.txt")
+        );
+        assert_eq!(
+            day_input(Path::new("inputs"), 16),
+            Path::new("inputs/day17.txt")
+        );
+    }
+
+    #[test]
+    fn bench_args_are_accepted() {
+        for name in DAYS {
+            let args = bench_args(name, Path::new("day.txt"));
+            let opt = Command::from_iter_safe(args).expect("valid arguments");
+
+            assert_eq!(opt.name(), name);
+        }
     }
 }
@@ -0,0 +1,56 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+/// A temporary directory holding `dayNN.txt` inputs.
+fn write_inputs(name: &str, inputs: &[(&str, &str)]) -> PathBuf {
+    let directory =
+        env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&directory).expect("temporary directory is writable");
+    for (file_name, contents) in inputs {
+        fs::write(directory.join(file_name), contents)
+            .expect("temporary input is writable");
+    }
+    directory
+}
+
+fn run_aoc(subcommand: &str, inputs: &[(&str, &str)]) -> std::process::Output {
+    let directory = write_inputs(subcommand, inputs);
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg(subcommand)
+        .arg(&directory)
+        .output()
+        .expect("aoc runs");
+    fs::remove_dir_all(&directory).expect("temporary directory is removable");
+    output
+}
+
+#[test]
+fn bench_fails_when_a_day_fails() {
+    let output = run_aoc("bench", &[("day17.txt", "target area: x=20..30\n")]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 days failed"));
+
+    let output = run_aoc(
+        "bench",
+        &[("day17.txt", "target area: x=20..30, y=-10..-5\n")],
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn all_runs_every_day_then_fails() {
+    let output = run_aoc(
+        "all",
+        &[
+            ("day06.txt", "3,4,3,1,2\n"),
+            ("day07.txt", "16,x\n"),
+            ("day17.txt", "target area: x=20..30\n"),
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success());
+    assert!(stdout.contains("Population after 80 days: 5934"));
+    assert!(stdout.contains("== day17: seventeen =="));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 days failed"));
+}
@@ -0,0 +1,18 @@
+use std::{env, fs, process::Command};
+
+#[test]
+fn empty_input_is_reported() {
+    let path = env::temp_dir()
+        .join(format!("aoc-eleven-empty-{}.input", std::process::id()));
+    fs::write(&path, "").expect("temporary input is writable");
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("eleven")
+        .arg(&path)
+        .output()
+        .expect("aoc runs");
+    fs::remove_file(&path).expect("temporary input is removable");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains(&format!("input file '{}' is empty", path.display())));
+}
@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+use aoc::day::{
+    eleven::OctopusEnergyLevelGrid,
+    nine::HeightMap,
+    sixteen::{bit_string, Operation, Transmission},
+    three,
+    twelve::CaveSystem,
+};
+
+#[test]
+fn cave_system_paths() {
+    let system = CaveSystem::parse(
+        ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"]
+            .into_iter(),
+    )
+    .expect("valid input");
+
+    assert_eq!(system.paths().expect("has a start").len(), 10);
+}
+
+#[test]
+fn cave_system_add_connection() {
+    let mut system = CaveSystem::new();
+    for (a, b) in [
+        ("start", "A"),
+        ("start", "b"),
+        ("A", "c"),
+        ("A", "b"),
+        ("b", "d"),
+        ("A", "end"),
+        ("b", "end"),
+    ] {
+        system.add_connection(a, b).expect("valid caves");
+    }
+
+    assert_eq!(system.paths().expect("has a start").len(), 10);
+    assert!(CaveSystem::default().add_connection("A", "Bc").is_err());
+}
+
+#[test]
+fn cave_system_paths_iter() {
+    let system = CaveSystem::parse(
+        ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"]
+            .into_iter(),
+    )
+    .expect("valid input");
+
+    let paths = system
+        .paths_iter()
+        .expect("has a start")
+        .collect::<Vec<_>>();
+    assert_eq!(paths.len(), 10);
+    assert!(paths.contains(&vec![
+        "start".to_owned(),
+        "b".to_owned(),
+        "end".to_owned()
+    ]));
+    assert!(CaveSystem::parse(["a-b"].into_iter())
+        .expect("valid input")
+        .paths_iter()
+        .is_err());
+}
+
+#[test]
+fn cave_system_with_endpoints() {
+    let system = CaveSystem::parse(
+        [
+            "begin-A", "begin-b", "A-c", "A-b", "b-d", "A-finish", "b-finish",
+        ]
+        .into_iter(),
+    )
+    .expect("valid input")
+    .with_endpoints("begin", "finish");
+
+    let paths = system.paths().expect("has a start");
+    assert_eq!(paths.len(), 10);
+    assert!(paths
+        .iter()
+        .all(|path| path.first() == Some(&"begin")
+            && path.last() == Some(&"finish")));
+}
+
+#[test]
+fn cave_system_path_tree() {
+    let system = CaveSystem::parse(
+        ["start-A", "start-b", "A-c", "A-b", "b-d", "A-end", "b-end"]
+            .into_iter(),
+    )
+    .expect("valid input");
+
+    let tree = system.path_tree();
+    assert!(tree.starts_with("start\n  A\n"));
+    assert_eq!(
+        tree.lines().filter(|line| line.ends_with("end")).count(),
+        10
+    );
+    assert_eq!(
+        CaveSystem::parse(["a-b"].into_iter())
+            .expect("valid input")
+            .path_tree(),
+        ""
+    );
+}
+
+#[test]
+fn height_map_risk_levels() {
+    let map = HeightMap::parse(
+        [
+            "2199943210",
+            "3987894921",
+            "9856789892",
+            "8767896789",
+            "9899965678",
+        ]
+        .into_iter(),
+    )
+    .expect("valid input");
+
+    assert_eq!(
+        map.risk_levels()
+            .iter()
+            .map(|risk| *risk as u32)
+            .sum::<u32>(),
+        15
+    );
+}
+
+#[test]
+fn height_map_basin_ids() {
+    let map = HeightMap::parse(
+        [
+            "2199943210",
+            "3987894921",
+            "9856789892",
+            "8767896789",
+            "9899965678",
+        ]
+        .into_iter(),
+    )
+    .expect("valid input");
+
+    let mappings = map.basin_mappings();
+
+    assert_eq!(mappings.basin_id_at(0, 5), mappings.basin_id_at(1, 6));
+    assert_ne!(mappings.basin_id_at(0, 0), mappings.basin_id_at(0, 9));
+    assert_eq!(mappings.basin_id_at(0, 2), None);
+    assert_eq!(mappings.basin_id_at(9, 9), None);
+}
+
+#[test]
+fn three_gamma_epsilon_and_life_support_ratings() {
+    let lines = [
+        "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100",
+        "10000", "11001", "00010", "01010",
+    ];
+
+    assert_eq!(three::gamma_epsilon(&lines).expect("valid input"), (22, 9));
+    assert_eq!(
+        three::life_support_ratings(&lines).expect("valid input"),
+        (23, 10)
+    );
+}
+
+#[test]
+fn transmission_version_sum_and_decode() {
+    let transmission =
+        Transmission::parse("8A004A801A8002F478").expect("valid input");
+    assert_eq!(transmission.version_sum(), 16);
+
+    let transmission = Transmission::parse("C200B40A82").expect("valid input");
+    assert_eq!(transmission.decode(), 3);
+}
+
+#[test]
+fn transmission_bit_string() {
+    assert_eq!(
+        bit_string("38006F45291200\n").expect("valid input"),
+        "00111000000000000110111101000101001010010001001000000000"
+    );
+    assert!(bit_string("38006G").is_err());
+}
+
+#[test]
+fn transmission_packet_counts() {
+    let transmission =
+        Transmission::parse("8A004A801A8002F478").expect("valid input");
+
+    assert_eq!(transmission.packet_counts(), (1, 3));
+}
+
+#[test]
+fn transmission_deepest_path() {
+    let transmission =
+        Transmission::parse("8A004A801A8002F478").expect("valid input");
+
+    assert_eq!(transmission.max_depth(), 3);
+    assert_eq!(transmission.deepest_path(), [0, 0, 0]);
+}
+
+#[test]
+fn transmission_operations_used() {
+    let transmission =
+        Transmission::parse("9C0141080250320F1802104A08").expect("valid input");
+
+    let operations = transmission.operations_used();
+    assert_eq!(operations.len(), 3);
+    assert!(operations.contains(&Operation::EqualTo));
+}
+
+#[test]
+fn octopus_energy_level_grid_cells() {
+    let grid =
+        OctopusEnergyLevelGrid::from_str("123\n456\n789").expect("valid input");
+
+    assert_eq!(grid.cells(), [vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+}
+
+#[test]
+fn octopus_energy_level_grid_first_flash_step() {
+    let mut grid =
+        OctopusEnergyLevelGrid::from_str("123\n456\n789").expect("valid input");
+
+    assert_eq!(grid.first_flash_step(2, 2, 10), Some(1));
+    assert_eq!(grid.cells()[2][2], 0);
+}
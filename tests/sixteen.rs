@@ -0,0 +1,89 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+fn write_input(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!(
+        "aoc-sixteen-{}-{}.input",
+        name,
+        std::process::id()
+    ));
+    fs::write(&path, contents).expect("temporary input is writable");
+    path
+}
+
+fn run_sixteen(input: &str, flags: &[&str]) -> std::process::Output {
+    let path = write_input(&flags.join(""), input);
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("sixteen")
+        .arg(&path)
+        .args(flags)
+        .output()
+        .expect("aoc runs");
+    fs::remove_file(&path).expect("temporary input is removable");
+    output
+}
+
+#[test]
+fn decode_only_prints_just_the_value() {
+    let output = run_sixteen("C200B40A82", &["--decode-only"]);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+}
+
+#[test]
+fn version_only_prints_just_the_version_sum() {
+    let output = run_sixteen("8A004A801A8002F478", &["--version-only"]);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "16\n");
+}
+
+#[test]
+fn decode_only_conflicts_with_version_only() {
+    let output =
+        run_sixteen("C200B40A82", &["--decode-only", "--version-only"]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn missing_input_exits_with_failure() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("sixteen")
+        .arg(env::temp_dir().join("aoc-sixteen-missing.input"))
+        .output()
+        .expect("aoc runs");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("failed to open file")
+    );
+}
+
+#[test]
+fn malformed_input_exits_with_failure() {
+    let output = run_sixteen("not hex", &[]);
+
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn inline_input_replaces_the_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["sixteen", "--inline", "D2FE28", "--decode-only"])
+        .output()
+        .expect("aoc runs");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2021\n");
+}
+
+#[test]
+fn inline_input_conflicts_with_a_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["sixteen", "input.txt", "--inline", "D2FE28"])
+        .output()
+        .expect("aoc runs");
+
+    assert!(!output.status.success());
+}
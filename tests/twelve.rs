@@ -0,0 +1,38 @@
+use std::process::Command;
+
+#[test]
+fn missing_start_is_reported() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["twelve", "--inline", "a-b"])
+        .output()
+        .expect("aoc runs");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing 'start'"));
+}
+
+#[test]
+fn any_prints_a_single_path() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["twelve", "--sample", "--any"])
+        .output()
+        .expect("aoc runs");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.starts_with("start,") && stdout.ends_with(",end\n"));
+}
+
+#[test]
+fn endpoints_can_be_renamed() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["twelve", "--inline", "begin-A\nA-b\nA-finish\nb-finish"])
+        .args(["--start", "begin", "--end", "finish"])
+        .output()
+        .expect("aoc runs");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("  begin,A,b,finish\n"));
+}